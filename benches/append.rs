@@ -0,0 +1,52 @@
+//! Compares appending a chunk by fully parsing a large PNG with `Png::append_raw`.
+//! Run with `cargo bench`.
+
+use std::time::{Duration, Instant};
+
+use pngme::png::{Chunk, ChunkType, Png};
+
+const IDAT_CHUNKS: usize = 128;
+const IDAT_SIZE: usize = 64 * 1024;
+const ITERATIONS: u32 = 10;
+
+fn large_png() -> Vec<u8> {
+    let mut png = Png::new_empty();
+    let idat = ChunkType::new_unchecked(*b"IDAT");
+    for i in 0..IDAT_CHUNKS {
+        png.append_chunk(Chunk::new(idat, vec![i as u8; IDAT_SIZE]));
+    }
+    png.as_bytes()
+}
+
+fn time<F: FnMut()>(mut run: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        run();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let bytes = large_png();
+    let chunk = Chunk::new(
+        ChunkType::new_unchecked(*b"ruSt"),
+        b"secret message".to_vec(),
+    );
+
+    let parsed = time(|| {
+        let mut png = Png::try_from(bytes.as_slice()).unwrap();
+        png.append_chunk(Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()));
+        std::hint::black_box(png.as_bytes());
+    });
+    let raw = time(|| {
+        std::hint::black_box(Png::append_raw(&bytes, &chunk).unwrap());
+    });
+
+    println!("file size:           {} bytes", bytes.len());
+    println!("parse + append:      {:?}", parsed);
+    println!("append_raw:          {:?}", raw);
+    println!(
+        "speedup:             {:.1}x",
+        parsed.as_secs_f64() / raw.as_secs_f64()
+    );
+}
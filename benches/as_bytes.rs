@@ -0,0 +1,57 @@
+//! Compares `Png::as_bytes`, which sizes its buffer up front, with collecting
+//! every chunk's bytes into a growing vector. Run with `cargo bench`.
+
+use std::time::{Duration, Instant};
+
+use pngme::png::{Chunk, ChunkType, Png};
+
+const IDAT_CHUNKS: usize = 512;
+const IDAT_SIZE: usize = 16 * 1024;
+const ITERATIONS: u32 = 20;
+
+fn large_png() -> Png {
+    let mut png = Png::new_empty();
+    let idat = ChunkType::new_unchecked(*b"IDAT");
+    for i in 0..IDAT_CHUNKS {
+        png.append_chunk(Chunk::new(idat, vec![i as u8; IDAT_SIZE]));
+    }
+    png
+}
+
+/// The previous implementation, kept here as the baseline
+fn collect_bytes(png: &Png) -> Vec<u8> {
+    let chunks: Vec<u8> = png
+        .chunks()
+        .iter()
+        .flat_map(|chunk| chunk.as_bytes())
+        .collect();
+    [png.header().to_vec(), chunks].concat()
+}
+
+fn time<F: FnMut()>(mut run: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        run();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let png = large_png();
+    assert_eq!(collect_bytes(&png), png.as_bytes());
+
+    let collected = time(|| {
+        std::hint::black_box(collect_bytes(&png));
+    });
+    let presized = time(|| {
+        std::hint::black_box(png.as_bytes());
+    });
+
+    println!("file size:           {} bytes", png.total_size());
+    println!("collect + concat:    {:?}", collected);
+    println!("presized as_bytes:   {:?}", presized);
+    println!(
+        "speedup:             {:.1}x",
+        collected.as_secs_f64() / presized.as_secs_f64()
+    );
+}
@@ -1,16 +1,76 @@
-use std::{path::PathBuf, str::FromStr};
+use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 use crate::chunk_type::ChunkType;
 
 #[derive(Debug, Parser)]
-#[command(author, version, about)]
+#[command(
+    author,
+    version,
+    about,
+    after_help = "Exit codes: 0 success, 1 other failure, 2 invalid arguments, 3 IO error, \
+                  4 invalid PNG, 5 chunk not found"
+)]
 pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
     #[command(subcommand)]
     pub commands: Commands,
 }
 
+#[derive(Debug, Default, Args)]
+pub struct GlobalArgs {
+    /// Trust stored CRCs instead of recomputing them for read-only commands.
+    /// Faster on huge files, but corrupted chunks go undetected
+    #[clap(long, global = true)]
+    pub fast: bool,
+
+    /// Do everything except writing the result to disk
+    #[clap(long, global = true)]
+    pub dry_run: bool,
+
+    /// Print more detail, with --dry-run this lists every planned step
+    #[clap(long, global = true)]
+    pub verbose: bool,
+
+    /// Allow overwriting an existing file, including editing the input file in place
+    #[clap(long, global = true)]
+    pub force: bool,
+
+    /// Only print essential data such as the decoded message, errors still go to stderr
+    #[clap(long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// When to color the output, `auto` disables it for pipes and when NO_COLOR is set
+    #[clap(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Default, Args)]
+pub struct BackupArgs {
+    /// Before writing over an existing file, copy it to `<file>.bak`, or to `<file><SUFFIX>`
+    /// with `--backup=SUFFIX`. The copy stands in for --force, which is then only
+    /// needed to replace an existing backup
+    #[clap(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak",
+        value_name = "SUFFIX"
+    )]
+    pub backup: Option<String>,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Encode the png file with the hidden message with chunk type
@@ -23,10 +83,61 @@ pub enum Commands {
     Remove(RemoveArgs),
 
     Print(PrintArgs),
+
+    /// Replace the message of an existing chunk, keeping its position
+    Replace(ReplaceArgs),
+
+    /// Change the type of the first matching chunk, keeping its position and data
+    Rename(RenameArgs),
+
+    /// Count the chunks in the png file without fully parsing it
+    Count(CountArgs),
+
+    /// Extract the data of every chunk into separate files
+    Extract(ExtractArgs),
+
+    /// Copy the first chunk of a type from one png file into another
+    AppendFrom(AppendFromArgs),
+
+    /// Remove ancillary chunks that duplicate an earlier chunk
+    Dedup(DedupArgs),
+
+    /// Fail if the file was produced by a pngme older than the minimum version
+    CheckVersion(CheckVersionArgs),
+
+    /// Check that every chunk CRC matches and the chunks follow the spec's ordering rules
+    Validate(ValidateArgs),
+
+    /// Print every chunk whose message contains the query
+    Search(SearchArgs),
+
+    /// Label each chunk as a known standard chunk or a custom one
+    Classify(ClassifyArgs),
+
+    /// List the chunks that differ between two png files
+    Compare(CompareArgs),
+
+    /// Report how many bytes the image data and the ancillary chunks take up
+    Stats(StatsArgs),
+
+    /// Show the chunks grouped by category as a tree
+    Tree(TreeArgs),
+
+    /// Explain what the letters of a chunk type code mean, no file needed
+    ChunkTypeInfo(ChunkTypeInfoArgs),
+
+    /// Apply an operation to every png file in a directory
+    Batch(BatchArgs),
+
+    /// Write every ancillary chunk to a directory along with a manifest.json describing them
+    Export(ExportArgs),
+
+    /// Append the chunks listed in an export manifest.json to a png file
+    Import(ImportArgs),
 }
 #[derive(Debug, Args)]
 pub struct EncodeArgs {
-    /// File path for Png file
+    /// File path for Png file, `-` reads it from stdin
     pub file_path: PathBuf,
 
     /// Chunk type
@@ -34,41 +145,430 @@ pub struct EncodeArgs {
     pub chunk_type: ChunkType,
 
     /// Message
-    pub message: String,
+    #[clap(required_unless_present = "messages", conflicts_with = "messages")]
+    pub message: Option<String>,
 
     /// Write the output PNG file to specific location
     pub output_file: Option<PathBuf>,
+
+    /// Write the output PNG file to this location, needed to give one alongside -m
+    #[clap(long = "output", conflicts_with = "output_file")]
+    pub output: Option<PathBuf>,
+
+    /// Message to store in its own chunk, can be repeated to append several in order
+    #[clap(short = 'm', long = "message")]
+    pub messages: Vec<String>,
+
+    /// Compress the message as zlib before storing it, decode detects and inflates it
+    #[cfg(feature = "compress")]
+    #[clap(long)]
+    pub compress: bool,
+
+    /// Only encode if a placeholder chunk of this type already exists
+    #[clap(long, value_parser = chunk_parser)]
+    pub only_if_present: Option<ChunkType>,
+
+    /// Reject messages whose stored chunk data would exceed this many bytes,
+    /// defaults to 64 MiB
+    #[clap(long, visible_alias = "max-message-size")]
+    pub max_message_bytes: Option<usize>,
+
+    /// Insert the chunk right before the first chunk of this type
+    #[clap(long, value_parser = chunk_parser)]
+    pub before: Option<ChunkType>,
+
+    /// Insert the chunk right after the first chunk of this type
+    #[clap(long, value_parser = chunk_parser, conflicts_with = "before")]
+    pub after: Option<ChunkType>,
+
+    /// Replace an existing chunk of this type and skip the write if nothing changed
+    #[clap(long, conflicts_with_all = ["before", "after"])]
+    pub replace_if_changed: bool,
+
+    /// Also stamp a provenance chunk recording the pngme version that wrote the file
+    #[clap(long)]
+    pub provenance: bool,
+
+    /// Also stamp a tIME chunk with the current UTC time, replacing any existing one
+    #[clap(long)]
+    pub stamp_time: bool,
+
+    /// Treat the message as base64 and store the decoded bytes
+    #[cfg(feature = "base64")]
+    #[clap(long)]
+    pub base64: bool,
+
+    /// Treat the message as hex, e.g. deadbeef, and store the decoded bytes
+    #[clap(long)]
+    #[cfg_attr(feature = "base64", clap(conflicts_with = "base64"))]
+    pub from_hex: bool,
+
+    /// Store the message as `keyword\0text` like a standard tEXt chunk, an iTXt
+    /// chunk type stores it in the international text layout instead
+    #[clap(long)]
+    #[cfg_attr(feature = "compress", clap(conflicts_with = "compress"))]
+    pub keyword: Option<String>,
+
+    /// Language tag of an iTXt message, e.g. `ja` or `en-GB`
+    #[clap(long, requires = "keyword")]
+    pub language: Option<String>,
+
+    /// Insert a copy of the message after every N existing ancillary chunks, e.g. as a
+    /// watermark. Copies only go between two ancillary chunks so the image stays valid
+    #[clap(long, conflicts_with_all = ["before", "after", "replace_if_changed"])]
+    pub every: Option<usize>,
+
+    /// Store this hex CRC, e.g. 0xdeadbeef, instead of the computed one. The new chunks
+    /// are not spec compliant and fail validation, meant for testing broken files
+    #[clap(long, value_parser = crc_parser, conflicts_with = "replace_if_changed")]
+    pub crc: Option<u32>,
+
+    #[command(flatten)]
+    pub backup: BackupArgs,
 }
 
 #[derive(Debug, Args)]
 pub struct DecodeArgs {
-    /// File path for Png file
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Chunk type
+    #[clap(value_parser = chunk_parser, required_unless_present = "pattern")]
+    pub chunk_type: Option<ChunkType>,
+
+    /// Print the chunk data encoded as base64
+    #[cfg(feature = "base64")]
+    #[clap(long)]
+    pub base64: bool,
+
+    /// Print the chunk data encoded as hex
+    #[clap(long)]
+    #[cfg_attr(feature = "base64", clap(conflicts_with = "base64"))]
+    pub hex: bool,
+
+    /// Print only the message exactly as stored, failing if it is not valid UTF-8
+    #[clap(long, conflicts_with = "hex")]
+    #[cfg_attr(feature = "base64", clap(conflicts_with = "base64"))]
+    pub raw: bool,
+
+    /// Decode every chunk whose type matches, `?` matches any letter, e.g. `??St`
+    #[clap(long, value_parser = pattern_parser, conflicts_with = "chunk_type")]
+    pub pattern: Option<String>,
+
+    /// Find the tEXt style chunk with this keyword and print only its text
+    #[clap(long)]
+    pub keyword: Option<String>,
+
+    /// Decode every chunk of the type instead of only the first
+    #[clap(long, conflicts_with = "keyword")]
+    pub all: bool,
+
+    /// End each message with a NUL byte instead of a newline, like `find -print0`,
+    /// so messages containing newlines can be split unambiguously. Combine with
+    /// --quiet to print the bare messages
+    #[clap(long)]
+    pub print0: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoveArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Chunk type, with --index the chunk at that position must have this type
+    #[clap(value_parser = chunk_parser, required_unless_present_any = ["index", "pattern"])]
+    pub chunk_type: Option<ChunkType>,
+
+    /// Remove every chunk whose type matches, `?` matches any letter, e.g. `??St`
+    #[clap(long, value_parser = pattern_parser, conflicts_with_all = ["chunk_type", "index"])]
+    pub pattern: Option<String>,
+
+    /// Remove every chunk of this type instead of only the first
+    #[clap(long, conflicts_with = "index")]
+    pub all: bool,
+
+    /// Remove the chunk at this zero based position instead of searching by type
+    #[clap(long)]
+    pub index: Option<usize>,
+
+    /// Write the result to this file instead of overwriting the input
+    #[clap(long = "output")]
+    pub output_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub backup: BackupArgs,
+}
+
+#[derive(Debug, Default, Args)]
+pub struct PrintArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Render control characters such as newlines as escape sequences
+    #[clap(long)]
+    pub escape: bool,
+
+    /// Show the raw bit 5 value of each chunk type byte
+    #[clap(long, hide = true)]
+    pub emit_chunk_type_bits: bool,
+
+    /// Show every chunk's data as an offset/hex/ASCII dump
+    #[clap(long)]
+    pub hex: bool,
+
+    /// Only print chunks of this type, can be repeated
+    #[clap(long = "type", value_parser = chunk_parser)]
+    pub types: Vec<ChunkType>,
+
+    /// Also print chunks with empty, blank or non UTF-8 data
+    #[clap(long)]
+    pub show_all: bool,
+
+    /// Show the byte offset in the file and the length of each chunk
+    #[clap(long)]
+    pub offsets: bool,
+
+    /// Stop after printing this many chunks, counted after the type filter
+    #[clap(long)]
+    pub limit: Option<usize>,
+
+    /// Also print any data appended after the IEND chunk
+    #[clap(long)]
+    pub show_trailing: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ReplaceArgs {
+    /// File path for Png file, `-` reads it from stdin
     pub file_path: PathBuf,
 
     /// Chunk type
     #[clap(value_parser = chunk_parser)]
     pub chunk_type: ChunkType,
+
+    /// New message
+    pub message: String,
+
+    #[command(flatten)]
+    pub backup: BackupArgs,
 }
 
 #[derive(Debug, Args)]
-pub struct RemoveArgs {
-    /// File path for Png file
+pub struct RenameArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Current chunk type
+    #[clap(value_parser = chunk_parser)]
+    pub from: ChunkType,
+
+    /// New chunk type
+    #[clap(value_parser = chunk_parser)]
+    pub to: ChunkType,
+}
+
+#[derive(Debug, Args)]
+pub struct CountArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Only count chunks whose type matches, `?` matches any letter, e.g. `??St`
+    #[clap(long, value_parser = pattern_parser)]
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ExtractArgs {
+    /// File path for Png file, `-` reads it from stdin
     pub file_path: PathBuf,
 
+    /// Directory to write the chunk files into
+    pub output_dir: PathBuf,
+
+    /// File name template supporting {index}, {type}, {length} and {crc}
+    #[clap(long, default_value = "{index}_{type}.bin")]
+    pub name_template: String,
+}
+
+#[derive(Debug, Args)]
+pub struct AppendFromArgs {
+    /// Png file to copy the chunk from
+    pub source: PathBuf,
+
+    /// Png file to append the chunk to, written back in place
+    pub dest: PathBuf,
+
     /// Chunk type
     #[clap(value_parser = chunk_parser)]
     pub chunk_type: ChunkType,
 }
 
 #[derive(Debug, Args)]
-pub struct PrintArgs {
-    /// File path for Png file
+pub struct DedupArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Only remove duplicates of this chunk type
+    #[clap(long = "type", value_parser = chunk_parser)]
+    pub chunk_type: Option<ChunkType>,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckVersionArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Minimum pngme version, e.g. 0.2.0
+    #[clap(long)]
+    pub min_version: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// List every chunk with whether its CRC passed or failed
+    #[clap(long)]
+    pub verify: bool,
+
+    /// Only check the 8 byte PNG signature without reading any chunks
+    #[clap(long, conflicts_with = "verify")]
+    pub is_png: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Text to look for in chunk messages
+    pub query: String,
+
+    /// Match the query regardless of letter case
+    #[clap(long)]
+    pub ignore_case: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ClassifyArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct CompareArgs {
+    /// Original png file
+    pub first: PathBuf,
+
+    /// Png file to compare against the original
+    pub second: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct TreeArgs {
+    /// File path for Png file, `-` reads it from stdin
     pub file_path: PathBuf,
 }
 
+#[derive(Debug, Args)]
+pub struct ChunkTypeInfoArgs {
+    /// Four letter chunk type code, e.g. ruSt. The reserved bit is reported rather than rejected
+    pub chunk_type: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Directory to write the chunk files and manifest.json into
+    pub output_dir: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// File path for Png file, `-` reads it from stdin
+    pub file_path: PathBuf,
+
+    /// Directory holding manifest.json and the chunk files written by export
+    pub input_dir: PathBuf,
+
+    /// Write the result to this file instead of overwriting the input
+    #[clap(long = "output")]
+    pub output_file: Option<PathBuf>,
+}
+
+/// Operations `batch` can apply to each file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BatchOperation {
+    /// Check every chunk CRC and the chunk order
+    Validate,
+    /// Remove ancillary chunks that duplicate an earlier chunk
+    Dedup,
+    /// Remove every chunk of the type given with --type
+    Remove,
+}
+
+#[derive(Debug, Args)]
+pub struct BatchArgs {
+    /// Directory holding the png files
+    pub dir: PathBuf,
+
+    /// Operation to apply to each file
+    #[clap(value_enum)]
+    pub operation: BatchOperation,
+
+    /// Also process png files in subdirectories
+    #[clap(long)]
+    pub recursive: bool,
+
+    /// Chunk type to remove, or the only type dedup removes duplicates of
+    #[clap(long = "type", value_parser = chunk_parser, required_if_eq("operation", "remove"))]
+    pub chunk_type: Option<ChunkType>,
+
+    /// Number of files to process in parallel, 0 uses one thread per CPU
+    #[clap(long, short = 'j', default_value_t = 1)]
+    pub jobs: usize,
+}
+
+/// Parses strictly so the CLI never writes a chunk type that fails to parse back
 fn chunk_parser(s: &str) -> Result<ChunkType, String> {
-    match ChunkType::from_str(s) {
+    match ChunkType::from_str_strict(s) {
         Ok(chunk_type) => Ok(chunk_type),
         Err(e) => Err(e.to_string()),
     }
 }
+
+fn crc_parser(s: &str) -> Result<u32, String> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    match digits.len() <= 8 && digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        true => u32::from_str_radix(digits, 16).map_err(|e| e.to_string()),
+        false => Err(format!(
+            "Expected up to 8 hex digits, e.g. `0xdeadbeef`, found {:?}",
+            s
+        )),
+    }
+}
+
+fn pattern_parser(s: &str) -> Result<String, String> {
+    match s.len() == 4
+        && s.bytes()
+            .all(|byte| byte == b'?' || byte.is_ascii_alphabetic())
+    {
+        true => Ok(s.to_string()),
+        false => Err(format!(
+            "Expected four letters or `?`, e.g. `??St`, found {:?}",
+            s
+        )),
+    }
+}
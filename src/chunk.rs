@@ -1,13 +1,16 @@
 use std::fmt::Display;
+use std::ops::Range;
+use std::str::FromStr;
 
 use anyhow::bail;
 use crc::{Crc, CRC_32_ISO_HDLC};
 
 use crate::chunk_type::ChunkType;
-use crate::Error;
 use crate::Result;
+use crate::{Error, PngError};
 
 const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Chunk {
     length: u32,
     chunk_type: ChunkType,
@@ -22,34 +25,144 @@ impl Chunk {
 
     pub const META_BYTES: usize = Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH + Chunk::CRC_LENGTH;
 
+    /// Builds a chunk from its type and data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data is longer than `u32::MAX` bytes, use `try_new` for data of unknown size
     pub fn new(chunk_type: ChunkType, data: Vec<u8>) -> Chunk {
-        let mut total_bytes: Vec<u8> = chunk_type.bytes().to_vec();
-        total_bytes.append(&mut data.clone());
-        Chunk {
+        match Chunk::try_new(chunk_type, data) {
+            Ok(chunk) => chunk,
+            Err(error) => panic!("{}", error),
+        }
+    }
+    /// Builds a chunk, failing instead of truncating the length if the data is
+    /// longer than `u32::MAX` bytes
+    pub fn try_new(chunk_type: ChunkType, data: Vec<u8>) -> Result<Chunk> {
+        let length = checked_length(data.len())?;
+        let crc = checksum(&chunk_type, &data);
+        Ok(Chunk {
+            length,
+            chunk_type,
+            data,
+            crc,
+        })
+    }
+    /// Builds a chunk that stores the given CRC instead of the computed one.
+    /// Unless the CRC happens to match, the chunk is not spec compliant and fails
+    /// validation when parsed back, which is only useful to produce malformed test files
+    pub fn new_unchecked(chunk_type: ChunkType, data: Vec<u8>, crc: u32) -> Result<Chunk> {
+        let length = checked_length(data.len())?;
+        Ok(Chunk {
+            length,
+            chunk_type,
+            data,
+            crc,
+        })
+    }
+    /// Parses the type string and builds the chunk, failing if the type is invalid
+    pub fn new_from_str(chunk_type: &str, data: Vec<u8>) -> Result<Chunk> {
+        Chunk::try_new(ChunkType::from_str_strict(chunk_type)?, data)
+    }
+    /// Starts a fluent builder, e.g. `Chunk::builder().chunk_type("ruSt")?.data_str("hi").build()`
+    pub fn builder() -> ChunkBuilder {
+        ChunkBuilder::default()
+    }
+    /// Parses a chunk while trusting its stored CRC instead of recomputing it.
+    /// This is faster on large inputs but corrupted data will not be detected.
+    /// The parsed CRC is kept as is, so `as_bytes` reproduces the input exactly.
+    pub fn try_from_trusted(chunk_data: &[u8]) -> Result<Chunk> {
+        let (chunk_type, data, crc) = split_fields(chunk_data)?;
+        Ok(Chunk {
             length: data.len() as u32,
             chunk_type,
-            data: data,
-            crc: CRC32.checksum(&total_bytes),
-        }
+            data,
+            crc,
+        })
+    }
+    /// Like `try_from_trusted`, but reuses the buffer of the raw bytes for the data
+    pub(crate) fn try_from_trusted_vec(mut chunk_data: Vec<u8>) -> Result<Chunk> {
+        let (chunk_type, data, crc) = locate_fields(&chunk_data)?;
+        chunk_data.truncate(data.end);
+        chunk_data.drain(..data.start);
+        Ok(Chunk {
+            length: chunk_data.len() as u32,
+            chunk_type,
+            data: chunk_data,
+            crc,
+        })
+    }
+    /// Replaces the data, updating the length and recomputing the CRC
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data is longer than `u32::MAX` bytes
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        self.length = match checked_length(data.len()) {
+            Ok(length) => length,
+            Err(error) => panic!("{}", error),
+        };
+        self.crc = checksum(&self.chunk_type, &data);
+        self.data = data;
+    }
+    /// Replaces the chunk type, recomputing the CRC since the type is part of the checksum
+    pub fn set_chunk_type(&mut self, chunk_type: ChunkType) {
+        self.crc = checksum(&chunk_type, &self.data);
+        self.chunk_type = chunk_type;
     }
     pub fn length(&self) -> u32 {
         self.length
     }
+    /// Size of the serialized chunk, the length of `as_bytes` without allocating it
+    pub fn byte_len(&self) -> usize {
+        Chunk::META_BYTES + self.length as usize
+    }
     pub fn chunk_type(&self) -> &ChunkType {
         &self.chunk_type
     }
-    fn data(&self) -> &[u8] {
+    /// The chunk type as a string, e.g. `"IHDR"`
+    pub fn type_str(&self) -> String {
+        self.chunk_type.to_string()
+    }
+    /// The four chunk type bytes
+    pub fn type_bytes(&self) -> [u8; 4] {
+        self.chunk_type.bytes()
+    }
+    pub fn data(&self) -> &[u8] {
         &self.data
     }
-    fn crc(&self) -> u32 {
+    pub fn crc(&self) -> u32 {
         self.crc
     }
+    /// Recomputes the CRC from the chunk type and data and compares it to the stored one
+    pub fn crc_is_valid(&self) -> bool {
+        checksum(&self.chunk_type, &self.data) == self.crc
+    }
+    pub fn is_critical(&self) -> bool {
+        self.chunk_type.is_critical()
+    }
+    pub fn is_ancillary(&self) -> bool {
+        !self.chunk_type.is_critical()
+    }
+    pub fn is_public(&self) -> bool {
+        self.chunk_type.is_public()
+    }
+    pub fn is_private(&self) -> bool {
+        !self.chunk_type.is_public()
+    }
+    pub fn is_safe_to_copy(&self) -> bool {
+        self.chunk_type.is_safe_to_copy()
+    }
     pub fn data_as_string(&self) -> Result<String> {
         match String::from_utf8(self.data.clone()) {
             Ok(val) => Ok(val),
             Err(_) => bail!("Unable to convert from vec<u8> to utf8"),
         }
     }
+    /// Best-effort rendering of the data, invalid UTF-8 becomes U+FFFD replacement characters
+    pub fn data_as_string_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.data).into_owned()
+    }
     pub fn as_bytes(&self) -> Vec<u8> {
         self.length()
             .to_be_bytes()
@@ -62,58 +175,185 @@ impl Chunk {
     }
 }
 
+/// Builder for a `Chunk`, the CRC is computed once on `build`
+#[derive(Debug, Default)]
+pub struct ChunkBuilder {
+    chunk_type: Option<ChunkType>,
+    data: Vec<u8>,
+}
+
+impl ChunkBuilder {
+    /// Sets the chunk type, failing if the string is not a valid chunk type.
+    /// Like the CLI this parses strictly, so a set reserved bit is rejected
+    pub fn chunk_type(mut self, chunk_type: &str) -> Result<Self> {
+        self.chunk_type = Some(ChunkType::from_str_strict(chunk_type)?);
+        Ok(self)
+    }
+    pub fn data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+    pub fn data_str(mut self, data: &str) -> Self {
+        self.data = data.as_bytes().to_vec();
+        self
+    }
+    /// Builds the chunk, failing if no chunk type was given
+    pub fn build(self) -> Result<Chunk> {
+        match self.chunk_type {
+            Some(chunk_type) => Chunk::try_new(chunk_type, self.data),
+            None => bail!("Chunk type is required to build a chunk"),
+        }
+    }
+}
+
+/// Converts a data length to the u32 stored in the chunk, failing rather than truncating
+fn checked_length(len: usize) -> Result<u32> {
+    match u32::try_from(len) {
+        Ok(length) => Ok(length),
+        Err(_) => bail!(PngError::DataTooLong(len)),
+    }
+}
+
+/// Computes the CRC over the chunk type and data bytes, feeding both to the
+/// digest in turn so the data is never copied
+fn checksum(chunk_type: &ChunkType, data: &[u8]) -> u32 {
+    #[cfg(test)]
+    tests::CRC_COMPUTATIONS.with(|count| count.set(count.get() + 1));
+
+    let mut digest = CRC32.digest();
+    digest.update(&chunk_type.bytes());
+    digest.update(data);
+    digest.finalize()
+}
+
+/// Returns how many bytes the chunk at the start of the input spans, checking its CRC
+/// when asked, without copying the data
+pub(crate) fn chunk_len(chunk_data: &[u8], check_crc: bool) -> Result<usize> {
+    let (chunk_type, data, crc) = locate_fields(chunk_data)?;
+    if check_crc {
+        let expected = checksum(&chunk_type, &chunk_data[data.clone()]);
+        if crc != expected {
+            bail!(PngError::BadCrc {
+                expected,
+                actual: crc
+            });
+        }
+    }
+    Ok(data.end + Chunk::CRC_LENGTH)
+}
+
+/// Splits raw chunk bytes into the chunk type, data and stored CRC
+fn split_fields(chunk_data: &[u8]) -> Result<(ChunkType, Vec<u8>, u32)> {
+    let (chunk_type, data, crc) = locate_fields(chunk_data)?;
+    Ok((chunk_type, chunk_data[data].to_vec(), crc))
+}
+
+/// Reads the chunk type and stored CRC, returning where the data lies instead of copying it
+fn locate_fields(chunk_data: &[u8]) -> Result<(ChunkType, Range<usize>, u32)> {
+    if chunk_data.len() < 12 {
+        bail!(PngError::TruncatedChunk);
+    }
+    let data_length = u32::from_be_bytes(
+        chunk_data[0..Chunk::DATA_LENGTH]
+            .to_owned()
+            .as_slice()
+            .try_into()
+            .expect("This should be 4 bytes"),
+    );
+    let chunk_type: [u8; 4] = chunk_data
+        [Chunk::DATA_LENGTH..Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH]
+        .to_vec()
+        .try_into()
+        .expect("This should be 4 bytes");
+    // A crafted length can be huge, so check the sums before slicing with them
+    let data_start = Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH;
+    let data_end = match usize::try_from(data_length)
+        .ok()
+        .and_then(|length| data_start.checked_add(length))
+    {
+        Some(end) => end,
+        None => bail!(PngError::TruncatedChunk),
+    };
+    let crc_end = match data_end.checked_add(Chunk::CRC_LENGTH) {
+        Some(end) if end <= chunk_data.len() => end,
+        _ => bail!(PngError::TruncatedChunk),
+    };
+    let message = data_start..data_end;
+    let crc = u32::from_be_bytes(
+        chunk_data[data_end..crc_end]
+            .try_into()
+            .expect("this should be 4 bytes"),
+    );
+    let chunk_type = ChunkType::try_from(chunk_type)?;
+    Ok((chunk_type, message, crc))
+}
+
 impl TryFrom<&[u8]> for Chunk {
     type Error = Error;
 
     fn try_from(chunk_data: &[u8]) -> Result<Self> {
-        if chunk_data.len() < 12 {
-            bail!("Length of the chunk is not enough to convert to Chunk");
+        let chunk = Chunk::try_from_trusted(chunk_data)?;
+        let expected = checksum(&chunk.chunk_type, &chunk.data);
+        match chunk.crc == expected {
+            true => Ok(chunk),
+            false => bail!(PngError::BadCrc {
+                expected,
+                actual: chunk.crc
+            }),
         }
-        let data_length = u32::from_be_bytes(
-            chunk_data[0..Chunk::DATA_LENGTH]
-                .to_owned()
-                .as_slice()
-                .try_into()
-                .expect("This should be 4 bytes"),
-        );
-        let chunk_type: [u8; 4] = chunk_data
-            [Chunk::DATA_LENGTH..Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH]
-            .to_vec()
-            .try_into()
-            .expect("This should be 4 bytes");
-        let message = chunk_data[Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH
-            ..Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH + data_length as usize]
-            .to_vec();
-        let crc = u32::from_be_bytes(
-            chunk_data[Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH + data_length as usize
-                ..Chunk::META_BYTES + data_length as usize]
-                .try_into()
-                .expect("this should be 4 bytes"),
-        );
-        let chunk_type = ChunkType::try_from(chunk_type).expect("Cannot convert");
-        let create_chunk = Chunk::new(chunk_type, message);
-        match crc == create_chunk.crc() {
-            true => Ok(create_chunk),
-            false => bail!("Invalid crc"),
+    }
+}
+/// Takes ownership of the raw bytes and reuses their buffer for the chunk data
+impl TryFrom<Vec<u8>> for Chunk {
+    type Error = Error;
+
+    fn try_from(chunk_data: Vec<u8>) -> Result<Self> {
+        let chunk = Chunk::try_from_trusted_vec(chunk_data)?;
+        let expected = checksum(&chunk.chunk_type, &chunk.data);
+        match chunk.crc == expected {
+            true => Ok(chunk),
+            false => bail!(PngError::BadCrc {
+                expected,
+                actual: chunk.crc
+            }),
         }
     }
 }
+/// Parses `TYPE:message`, e.g. `ruSt:hello world`, splitting on the first colon.
+/// The type is parsed strictly, so a set reserved bit is an error
+impl FromStr for Chunk {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some((chunk_type, message)) => Chunk::try_new(
+                ChunkType::from_str_strict(chunk_type)?,
+                message.as_bytes().to_vec(),
+            ),
+            None => bail!("Expected TYPE:message, found no colon"),
+        }
+    }
+}
+
+/// Writes the message as plain text, or a `<binary N bytes>` placeholder if it is not UTF-8
 impl Display for Chunk {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let msg = String::from_utf8(self.data().to_vec());
-
-        match msg {
-            Ok(msg) => write!(f, "{:?}", msg),
-            Err(e) => write!(f, "{:?}", e),
+        match std::str::from_utf8(self.data()) {
+            Ok(msg) => write!(f, "{}", msg),
+            Err(_) => write!(f, "<binary {} bytes>", self.length()),
         }
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use crate::chunk_type::ChunkType;
-    use std::str::FromStr;
+    use std::cell::Cell;
+
+    thread_local! {
+        /// Number of CRCs computed on the current thread, used to check the trusted parser
+        pub(crate) static CRC_COMPUTATIONS: Cell<usize> = const { Cell::new(0) };
+    }
 
     fn testing_chunk() -> Chunk {
         let data_length: u32 = 42;
@@ -144,18 +384,164 @@ mod tests {
         assert_eq!(chunk.crc(), 2882656334);
     }
 
+    #[test]
+    fn test_try_new_length_check() {
+        let chunk = Chunk::try_new(ChunkType::from_str("RuSt").unwrap(), b"data".to_vec());
+        assert_eq!(chunk.unwrap().length(), 4);
+
+        // Allocating more than 4 GiB is too much for a unit test, so check the helper directly
+        assert_eq!(checked_length(u32::MAX as usize).unwrap(), u32::MAX);
+        let too_long = u32::MAX as usize + 1;
+        assert_eq!(
+            checked_length(too_long)
+                .unwrap_err()
+                .downcast_ref::<PngError>(),
+            Some(&PngError::DataTooLong(too_long))
+        );
+    }
+
+    #[test]
+    fn test_new_from_str() {
+        let chunk = Chunk::new_from_str("RuSt", b"message".to_vec()).unwrap();
+        assert_eq!(
+            chunk,
+            Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"message".to_vec())
+        );
+        assert!(Chunk::new_from_str("Ru1t", Vec::new()).is_err());
+        assert!(Chunk::new_from_str("Rust", Vec::new()).is_err());
+        assert!(Chunk::new_from_str("RuStt", Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_chunk_builder() {
+        let built = Chunk::builder()
+            .chunk_type("ruSt")
+            .unwrap()
+            .data_str("hello")
+            .build()
+            .unwrap();
+        let expected = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hello".to_vec());
+        assert_eq!(built.as_bytes(), expected.as_bytes());
+
+        let built = Chunk::builder()
+            .chunk_type("ruSt")
+            .unwrap()
+            .data(b"hello".to_vec())
+            .build()
+            .unwrap();
+        assert_eq!(built.as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_chunk_builder_errors() {
+        assert!(Chunk::builder().chunk_type("ru1t").is_err());
+        assert!(Chunk::builder().chunk_type("Rust").is_err());
+        assert!(Chunk::builder().data_str("hello").build().is_err());
+    }
+
+    #[test]
+    fn test_chunk_eq() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let first = Chunk::new(chunk_type, b"same data".to_vec());
+        let second = Chunk::new(chunk_type, b"same data".to_vec());
+        assert_eq!(first, second);
+        assert_ne!(first, Chunk::new(chunk_type, b"other data".to_vec()));
+        assert_eq!(Chunk::try_from(first.as_bytes().as_slice()).unwrap(), first);
+    }
+
+    #[test]
+    fn test_chunk_clone() {
+        let chunk = testing_chunk();
+        let mut copy = chunk.clone();
+        assert_eq!(copy, chunk);
+        copy.set_data(b"changed".to_vec());
+        assert_ne!(copy, chunk);
+        assert_eq!(
+            chunk.data_as_string().unwrap(),
+            testing_chunk().data_as_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_set_data() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let mut chunk = Chunk::new(chunk_type, b"old".to_vec());
+        chunk.set_data(b"This is where your secret message will be!".to_vec());
+        let expected = Chunk::new(
+            chunk_type,
+            b"This is where your secret message will be!".to_vec(),
+        );
+        assert_eq!(chunk.length(), 42);
+        assert_eq!(chunk.crc(), 2882656334);
+        assert_eq!(chunk, expected);
+    }
+
+    #[test]
+    fn test_data_as_string_lossy() {
+        let chunk = Chunk::new(
+            ChunkType::from_str("RuSt").unwrap(),
+            b"caf\xe9 au lait\xff".to_vec(),
+        );
+        assert!(chunk.data_as_string().is_err());
+        assert_eq!(chunk.data_as_string_lossy(), "caf\u{fffd} au lait\u{fffd}");
+
+        let text = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"plain".to_vec());
+        assert_eq!(text.data_as_string_lossy(), text.data_as_string().unwrap());
+    }
+
+    #[test]
+    fn test_set_chunk_type() {
+        let data = b"This is where your secret message will be!".to_vec();
+        let mut chunk = Chunk::new(ChunkType::from_str("teSt").unwrap(), data.clone());
+        chunk.set_chunk_type(ChunkType::from_str("RuSt").unwrap());
+        assert_eq!(chunk.crc(), 2882656334);
+        assert_eq!(
+            chunk,
+            Chunk::new(ChunkType::from_str("RuSt").unwrap(), data)
+        );
+    }
+
     #[test]
     fn test_chunk_length() {
         let chunk = testing_chunk();
         assert_eq!(chunk.length(), 42);
     }
 
+    #[test]
+    fn test_chunk_byte_len() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.byte_len(), chunk.as_bytes().len());
+        let empty = Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new());
+        assert_eq!(empty.byte_len(), Chunk::META_BYTES);
+    }
+
     #[test]
     fn test_chunk_type() {
         let chunk = testing_chunk();
         assert_eq!(chunk.chunk_type().to_string(), String::from("RuSt"));
     }
 
+    #[test]
+    fn test_chunk_type_shortcuts() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.type_str(), "RuSt");
+        assert_eq!(chunk.type_bytes(), *b"RuSt");
+        assert_eq!(chunk.type_str(), chunk.chunk_type().to_string());
+    }
+
+    #[test]
+    fn test_chunk_type_properties() {
+        for chunk_type in ["RuSt", "ruSt", "RUST", "rust"] {
+            let chunk_type = ChunkType::new_unchecked(chunk_type.as_bytes().try_into().unwrap());
+            let chunk = Chunk::new(chunk_type, Vec::new());
+            assert_eq!(chunk.is_critical(), chunk_type.is_critical());
+            assert_eq!(chunk.is_ancillary(), !chunk_type.is_critical());
+            assert_eq!(chunk.is_public(), chunk_type.is_public());
+            assert_eq!(chunk.is_private(), !chunk_type.is_public());
+            assert_eq!(chunk.is_safe_to_copy(), chunk_type.is_safe_to_copy());
+        }
+    }
+
     #[test]
     fn test_chunk_string() {
         let chunk = testing_chunk();
@@ -218,6 +604,134 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    fn test_chunk_error_variants() {
+        let mut bytes = testing_chunk().as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let error = Chunk::try_from(bytes.as_ref()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::BadCrc {
+                expected: 2882656334,
+                actual: 2882656334 ^ 0xff
+            })
+        );
+
+        let error = Chunk::try_from(&bytes[..8]).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::TruncatedChunk)
+        );
+    }
+
+    #[test]
+    fn test_zero_length_chunk() {
+        let chunk = Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new());
+        assert_eq!(chunk.length(), 0);
+        assert_eq!(chunk.crc(), 0xae426082);
+        assert_eq!(&chunk.data_as_string().unwrap(), "");
+
+        let bytes = chunk.as_bytes();
+        assert_eq!(
+            bytes,
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]
+        );
+        let parsed = Chunk::try_from(bytes.as_ref()).unwrap();
+        assert_eq!(parsed, chunk);
+        assert!(parsed.data().is_empty());
+        assert!(Chunk::try_from(&bytes[..11]).is_err());
+    }
+
+    #[test]
+    fn test_checksum_matches_concatenated_crc() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let data: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i * 31 % 251) as u8).collect();
+        let mut total_bytes = chunk_type.bytes().to_vec();
+        total_bytes.extend_from_slice(&data);
+
+        let chunk = Chunk::new(chunk_type, data);
+        assert_eq!(chunk.crc(), CRC32.checksum(&total_bytes));
+        assert_eq!(testing_chunk().crc(), 2882656334);
+    }
+
+    #[test]
+    fn test_crc_is_valid() {
+        assert!(testing_chunk().crc_is_valid());
+
+        let mut bytes = testing_chunk().as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let tampered = Chunk::try_from_trusted(&bytes).unwrap();
+        assert!(!tampered.crc_is_valid());
+
+        let forced =
+            Chunk::new_unchecked(ChunkType::from_str("RuSt").unwrap(), b"data".to_vec(), 1)
+                .unwrap();
+        assert_eq!(forced.crc(), 1);
+        assert!(!forced.crc_is_valid());
+        assert!(Chunk::try_from(forced.as_bytes().as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_chunk_from_vec() {
+        let bytes = testing_chunk().as_bytes();
+        let chunk = Chunk::try_from(bytes.clone()).unwrap();
+        assert_eq!(chunk, Chunk::try_from(bytes.as_ref()).unwrap());
+        assert_eq!(chunk.as_bytes(), bytes);
+
+        let mut corrupted = bytes;
+        corrupted[10] ^= 0xff;
+        assert!(Chunk::try_from(corrupted).is_err());
+        assert!(Chunk::try_from(vec![0; 8]).is_err());
+    }
+
+    #[test]
+    fn test_huge_declared_length_is_an_error() {
+        let mut bytes = testing_chunk().as_bytes();
+        bytes[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let error = Chunk::try_from(bytes.as_ref()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::TruncatedChunk)
+        );
+        assert!(Chunk::try_from_trusted(&bytes).is_err());
+        assert!(Chunk::try_from(bytes).is_err());
+
+        let mut bytes = testing_chunk().as_bytes();
+        bytes[..4].copy_from_slice(&43u32.to_be_bytes());
+        assert!(Chunk::try_from(bytes.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_trusted_chunk_skips_crc() {
+        let bytes = testing_chunk().as_bytes();
+        CRC_COMPUTATIONS.with(|count| count.set(0));
+        let chunk = Chunk::try_from_trusted(&bytes).unwrap();
+        assert_eq!(CRC_COMPUTATIONS.with(|count| count.get()), 0);
+        assert_eq!(chunk.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_chunk_from_str() {
+        let chunk = Chunk::from_str("ruSt:hello: world").unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "ruSt");
+        assert_eq!(chunk.data_as_string().unwrap(), "hello: world");
+
+        let chunk: Chunk = "ruSt:".parse().unwrap();
+        assert_eq!(chunk.length(), 0);
+    }
+
+    #[test]
+    fn test_chunk_from_str_malformed() {
+        assert!(Chunk::from_str("ruSt hello").is_err());
+        assert!(Chunk::from_str("ru1t:hello").is_err());
+        assert!(Chunk::from_str("rust1:hello").is_err());
+        assert!(Chunk::from_str(":hello").is_err());
+        // The reserved bit is set, such a chunk would fail validation
+        assert!(Chunk::from_str("Rust:hi").is_err());
+    }
+
     #[test]
     pub fn test_chunk_trait_impls() {
         let data_length: u32 = 42;
@@ -238,4 +752,77 @@ mod tests {
 
         let _chunk_string = format!("{}", chunk);
     }
+
+    #[test]
+    fn test_chunk_display() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let chunk = Chunk::new(chunk_type, b"hello \"world\"\n".to_vec());
+        assert_eq!(chunk.to_string(), "hello \"world\"\n");
+
+        let binary = Chunk::new(chunk_type, vec![0xff, 0xfe, 0x00]);
+        assert_eq!(binary.to_string(), "<binary 3 bytes>");
+    }
+
+    /// Small xorshift generator so the round-trip properties run without extra crates
+    struct Xorshift(u64);
+
+    impl Xorshift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+        fn chunk_type(&mut self) -> ChunkType {
+            let mut bytes = [0; 4];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                let letter = b'A' + self.below(26) as u8;
+                // The reserved third byte must stay uppercase to be valid
+                *byte = match i != 2 && self.next().is_multiple_of(2) {
+                    true => letter.to_ascii_lowercase(),
+                    false => letter,
+                };
+            }
+            ChunkType::try_from(bytes).unwrap()
+        }
+        fn data(&mut self) -> Vec<u8> {
+            let length = match self.below(8) {
+                0 => 0,
+                _ => self.below(4096),
+            };
+            (0..length).map(|_| self.next() as u8).collect()
+        }
+    }
+
+    #[test]
+    fn test_round_trip_property() {
+        let mut rng = Xorshift(0x5eed_1234_abcd_0042);
+        for _ in 0..500 {
+            let chunk = Chunk::new(rng.chunk_type(), rng.data());
+            let bytes = chunk.as_bytes();
+            assert_eq!(bytes.len(), chunk.byte_len());
+            assert_eq!(Chunk::try_from(bytes.as_slice()).unwrap(), chunk);
+            assert_eq!(Chunk::try_from_trusted(&bytes).unwrap(), chunk);
+            assert_eq!(Chunk::try_from(bytes).unwrap(), chunk);
+        }
+    }
+
+    #[test]
+    fn test_truncated_and_corrupted_property() {
+        let mut rng = Xorshift(0x0dd_ba11_c0ff_ee00);
+        for _ in 0..500 {
+            let chunk = Chunk::new(rng.chunk_type(), rng.data());
+            let bytes = chunk.as_bytes();
+            let cut = rng.below(bytes.len());
+            assert!(Chunk::try_from(&bytes[..cut]).is_err());
+
+            let mut corrupted = bytes.clone();
+            let position = Chunk::DATA_LENGTH + rng.below(bytes.len() - Chunk::DATA_LENGTH);
+            corrupted[position] ^= 1 << rng.below(8);
+            assert!(Chunk::try_from(corrupted.as_slice()).is_err());
+        }
+    }
 }
@@ -1,16 +1,95 @@
 use anyhow::bail;
 
-use crate::Error;
+use crate::{Error, PngError};
 use std::{char, fmt::Display, str::FromStr};
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+/// Chunk types defined by the PNG specification and its registered extensions
+const KNOWN_CHUNK_TYPES: [(&[u8; 4], &str); 23] = [
+    (b"IHDR", "image header"),
+    (b"PLTE", "palette"),
+    (b"IDAT", "image data"),
+    (b"IEND", "image trailer"),
+    (b"tRNS", "transparency"),
+    (b"cHRM", "primary chromaticities"),
+    (b"gAMA", "image gamma"),
+    (b"iCCP", "embedded ICC profile"),
+    (b"sBIT", "significant bits"),
+    (b"sRGB", "standard RGB color space"),
+    (b"cICP", "coding-independent code points"),
+    (b"tEXt", "textual data"),
+    (b"zTXt", "compressed textual data"),
+    (b"iTXt", "international textual data"),
+    (b"bKGD", "background color"),
+    (b"hIST", "palette histogram"),
+    (b"pHYs", "physical pixel dimensions"),
+    (b"sPLT", "suggested palette"),
+    (b"eXIf", "exchangeable image file data"),
+    (b"tIME", "last modification time"),
+    (b"acTL", "animation control"),
+    (b"fcTL", "frame control"),
+    (b"fdAT", "frame data"),
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct ChunkType(u8, u8, u8, u8);
 #[allow(dead_code)]
 impl ChunkType {
+    /// Creates a chunk type, failing if the bytes are not a valid chunk type
+    pub fn new(bytes: [u8; 4]) -> Result<Self, Error> {
+        ChunkType::try_from(bytes)
+    }
+    /// Creates a chunk type without any validation.
+    /// This bypasses `is_valid`, so the caller must ensure the bytes form a valid type
+    pub fn new_unchecked(bytes: [u8; 4]) -> Self {
+        Self(bytes[0], bytes[1], bytes[2], bytes[3])
+    }
     pub fn bytes(&self) -> [u8; 4] {
         [self.0, self.1, self.2, self.3]
     }
 
+    /// Bit 5 of each of the four type bytes, in byte order
+    pub fn property_bits(&self) -> [u8; 4] {
+        self.bytes().map(|byte| (byte >> 5) & 1)
+    }
+
+    /// Returns a copy with the first byte made critical (uppercase) or ancillary (lowercase)
+    pub fn set_critical(self, critical: bool) -> ChunkType {
+        ChunkType(set_bit_5(self.0, !critical), self.1, self.2, self.3)
+    }
+    /// Returns a copy with the second byte made public (uppercase) or private (lowercase)
+    pub fn set_public(self, public: bool) -> ChunkType {
+        ChunkType(self.0, set_bit_5(self.1, !public), self.2, self.3)
+    }
+    /// Returns a copy with the fourth byte made safe (lowercase) or unsafe (uppercase) to copy
+    pub fn set_safe_to_copy(self, safe_to_copy: bool) -> ChunkType {
+        ChunkType(self.0, self.1, self.2, set_bit_5(self.3, safe_to_copy))
+    }
+
+    /// Matches a four character pattern where `?` stands for any ASCII letter, e.g. `??St`
+    pub fn matches_pattern(&self, pattern: &str) -> bool {
+        pattern.len() == 4
+            && pattern
+                .bytes()
+                .zip(self.bytes())
+                .all(|(expected, byte)| match expected {
+                    b'?' => byte.is_ascii_alphabetic(),
+                    _ => expected == byte,
+                })
+    }
+    /// Describes a standard chunk type, or returns None for custom types
+    pub fn description(&self) -> Option<&'static str> {
+        KNOWN_CHUNK_TYPES
+            .iter()
+            .find(|(bytes, _)| **bytes == self.bytes())
+            .map(|&(_, description)| description)
+    }
+
+    /// Parses a chunk type like `from_str`, but also requires the reserved bit to be valid,
+    /// applying the same rules as `TryFrom<[u8; 4]>`
+    pub fn from_str_strict(s: &str) -> Result<Self, Error> {
+        ChunkType::try_from(ChunkType::from_str(s)?.bytes())
+    }
+
     fn is_valid(&self) -> bool {
         self.is_valid_ascii() && self.is_reserved_bit_valid()
     }
@@ -20,35 +99,47 @@ impl ChunkType {
             .all(|&i| (65..=90).contains(&i) || (97..=122).contains(&i))
     }
     /// A type code is critical if bit 5 (value 32) of the first byte is 0
-    fn is_critical(&self) -> bool {
+    pub fn is_critical(&self) -> bool {
         (self.0 >> 5) & 1 == 0
     }
     /// A type code is public if bit 5 (value 32) of the second byte is 0
-    fn is_public(&self) -> bool {
+    pub fn is_public(&self) -> bool {
         (self.1 >> 5) & 1 == 0
     }
     /// Bit 5 of the third byte is reserved and must be 0
-    fn is_reserved_bit_valid(&self) -> bool {
+    pub fn is_reserved_bit_valid(&self) -> bool {
         (self.2 >> 5) & 1 == 0
     }
     /// A type code is safe to copy if bit 5 (value 32) of the fourth byte is 1
-    fn is_safe_to_copy(&self) -> bool {
+    pub fn is_safe_to_copy(&self) -> bool {
         (self.3 >> 5) & 1 == 1
     }
 }
 
+/// Sets or clears bit 5, which switches the case of an ASCII letter
+fn set_bit_5(byte: u8, set: bool) -> u8 {
+    match set {
+        true => byte | 0b0010_0000,
+        false => byte & !0b0010_0000,
+    }
+}
+
 impl TryFrom<[u8; 4]> for ChunkType {
     type Error = Error;
 
     fn try_from(value: [u8; 4]) -> Result<Self, Self::Error> {
-        let chunk = Self(value[0], value[1], value[2], value[3]);
+        let chunk = ChunkType::new_unchecked(value);
         match chunk.is_valid() {
             true => Ok(chunk),
-            false => bail!("Invalid chunk type inputted"),
+            false => bail!(PngError::InvalidChunkType(
+                String::from_utf8_lossy(&value).into_owned()
+            )),
         }
     }
 }
 
+/// Only requires four ASCII letters, so a type with an invalid reserved bit such as `Rust`
+/// parses and reports itself through `is_valid`. Use `ChunkType::from_str_strict` to reject it
 impl FromStr for ChunkType {
     type Err = Error;
 
@@ -56,16 +147,28 @@ impl FromStr for ChunkType {
         let binding = s.to_owned();
         let bytes = String::as_bytes(&binding);
         if bytes.len() != 4 {
-            bail!("The input bytes not equals to 4");
+            bail!(PngError::InvalidChunkType(s.to_string()));
         }
         let chunk_type = ChunkType(bytes[0], bytes[1], bytes[2], bytes[3]);
         if !chunk_type.is_valid_ascii() {
-            bail!("Invalid ascii input")
+            bail!(PngError::InvalidChunkType(s.to_string()))
         }
         Ok(chunk_type)
     }
 }
 
+impl From<ChunkType> for [u8; 4] {
+    fn from(chunk_type: ChunkType) -> Self {
+        chunk_type.bytes()
+    }
+}
+
+impl From<&ChunkType> for String {
+    fn from(chunk_type: &ChunkType) -> Self {
+        chunk_type.to_string()
+    }
+}
+
 impl Display for ChunkType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -90,6 +193,24 @@ mod tests {
         assert_eq!(expected, actual.bytes());
     }
 
+    #[test]
+    pub fn test_chunk_type_new() {
+        let chunk = ChunkType::new(*b"RuSt").unwrap();
+        assert_eq!(chunk, ChunkType::from_str("RuSt").unwrap());
+        assert!(ChunkType::new([82, 117, 83, 200]).is_err());
+        assert!(ChunkType::new(*b"Ru1t").is_err());
+    }
+
+    #[test]
+    pub fn test_chunk_type_new_unchecked() {
+        let chunk = ChunkType::new_unchecked(*b"RuSt");
+        assert_eq!(chunk, ChunkType::new(*b"RuSt").unwrap());
+
+        let chunk = ChunkType::new_unchecked([82, 117, 83, 200]);
+        assert_eq!(chunk.bytes(), [82, 117, 83, 200]);
+        assert!(!chunk.is_valid());
+    }
+
     #[test]
     pub fn test_chunk_type_from_str() {
         let expected = ChunkType::try_from([82, 117, 83, 116]).unwrap();
@@ -145,6 +266,40 @@ mod tests {
         assert!(!chunk.is_safe_to_copy());
     }
 
+    #[test]
+    pub fn test_chunk_type_property_bits() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(chunk.property_bits(), [0, 1, 0, 1]);
+        assert_eq!(chunk.property_bits()[0] == 0, chunk.is_critical());
+        assert_eq!(chunk.property_bits()[1] == 0, chunk.is_public());
+        assert_eq!(chunk.property_bits()[2] == 0, chunk.is_reserved_bit_valid());
+        assert_eq!(chunk.property_bits()[3] == 1, chunk.is_safe_to_copy());
+    }
+
+    #[test]
+    pub fn test_chunk_type_set_property_bits() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+
+        let ancillary = chunk.set_critical(false);
+        assert!(!ancillary.is_critical());
+        assert_eq!(&ancillary.to_string(), "ruSt");
+        assert!(ancillary.set_critical(true).is_critical());
+
+        let public = chunk.set_public(true);
+        assert!(public.is_public());
+        assert_eq!(&public.to_string(), "RUSt");
+        assert!(!public.set_public(false).is_public());
+
+        let unsafe_to_copy = chunk.set_safe_to_copy(false);
+        assert!(!unsafe_to_copy.is_safe_to_copy());
+        assert_eq!(&unsafe_to_copy.to_string(), "RuST");
+        assert!(unsafe_to_copy.set_safe_to_copy(true).is_safe_to_copy());
+
+        for toggled in [ancillary, public, unsafe_to_copy] {
+            assert!(toggled.is_valid());
+        }
+    }
+
     #[test]
     pub fn test_valid_chunk_is_valid() {
         let chunk = ChunkType::from_str("RuSt").unwrap();
@@ -160,6 +315,59 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    pub fn test_chunk_type_from_str_strict() {
+        // The lenient parser accepts a bad reserved bit that the byte conversion rejects
+        assert!(ChunkType::from_str("Rust").is_ok());
+        assert!(ChunkType::try_from(*b"Rust").is_err());
+
+        let error = ChunkType::from_str_strict("Rust").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::InvalidChunkType("Rust".to_string()))
+        );
+        assert!(ChunkType::from_str_strict("Ru1t").is_err());
+        assert_eq!(
+            ChunkType::from_str_strict("RuSt").unwrap(),
+            ChunkType::try_from(*b"RuSt").unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_invalid_chunk_type_error() {
+        let error = ChunkType::from_str("Ru1t").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::InvalidChunkType("Ru1t".to_string()))
+        );
+        let error = ChunkType::try_from(*b"Rust").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::InvalidChunkType("Rust".to_string()))
+        );
+    }
+
+    #[test]
+    pub fn test_chunk_type_description() {
+        let chunk = ChunkType::from_str("IHDR").unwrap();
+        assert_eq!(chunk.description(), Some("image header"));
+        let chunk = ChunkType::from_str("tEXt").unwrap();
+        assert_eq!(chunk.description(), Some("textual data"));
+        let chunk = ChunkType::from_str("ruSt").unwrap();
+        assert_eq!(chunk.description(), None);
+    }
+
+    #[test]
+    pub fn test_chunk_type_matches_pattern() {
+        let chunk = ChunkType::from_str("ruSt").unwrap();
+        assert!(chunk.matches_pattern("??St"));
+        assert!(chunk.matches_pattern("????"));
+        assert!(chunk.matches_pattern("ruSt"));
+        assert!(!chunk.matches_pattern("??st"));
+        assert!(!chunk.matches_pattern("?St"));
+        assert!(!chunk.matches_pattern("r?S?t"));
+    }
+
     #[test]
     pub fn test_chunk_type_string() {
         let chunk = ChunkType::from_str("RuSt").unwrap();
@@ -173,4 +381,13 @@ mod tests {
         let _chunk_string = format!("{}", chunk_type_1);
         let _are_chunks_equal = chunk_type_1 == chunk_type_2;
     }
+
+    #[test]
+    pub fn test_chunk_type_into_bytes_and_string() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let bytes: [u8; 4] = chunk_type.into();
+        assert_eq!(bytes, *b"RuSt");
+        let string: String = (&chunk_type).into();
+        assert_eq!(string, "RuSt");
+    }
 }
@@ -1,89 +1,3630 @@
 use std::convert::TryFrom;
 use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-use anyhow::bail;
+use anyhow::{bail, Context};
 
-use crate::args::{DecodeArgs, EncodeArgs, PrintArgs, RemoveArgs};
-use crate::png::{Chunk, Png};
-use crate::Result;
+use crate::args::{
+    AppendFromArgs, BackupArgs, BatchArgs, BatchOperation, CheckVersionArgs, ChunkTypeInfoArgs,
+    ClassifyArgs, ColorChoice, CompareArgs, CountArgs, DecodeArgs, DedupArgs, EncodeArgs,
+    ExportArgs, ExtractArgs, GlobalArgs, ImportArgs, PrintArgs, RemoveArgs, RenameArgs,
+    ReplaceArgs, SearchArgs, StatsArgs, TreeArgs, ValidateArgs,
+};
+#[cfg(feature = "compress")]
+use crate::compress;
+use crate::encoding;
+use crate::manifest::{self, ManifestEntry};
+use crate::png::{AnimationControl, Chunk, ChunkType, Png};
+use crate::text::{self, InternationalText};
+use crate::time::ModificationTime;
+use crate::{PngError, Result};
+
+/// Chunk type of the provenance chunk, its data is `pngme <version>`
+const PROVENANCE_CHUNK: &str = "prOv";
+
+/// Largest message encode accepts unless `--max-message-bytes` says otherwise
+const DEFAULT_MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
 
 /// Encodes a message into a PNG file and saves the result
-pub fn encode(args: EncodeArgs) -> Result<()> {
+pub fn encode(args: EncodeArgs, global: &GlobalArgs) -> Result<()> {
+    encode_to(args, global, &mut io::stdout().lock())
+}
+
+fn encode_to<W: Write>(args: EncodeArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
     let EncodeArgs {
         file_path,
         chunk_type,
         message,
         output_file,
+        output,
+        messages,
+        #[cfg(feature = "compress")]
+        compress,
+        only_if_present,
+        max_message_bytes,
+        before,
+        after,
+        replace_if_changed,
+        provenance,
+        stamp_time,
+        #[cfg(feature = "base64")]
+        base64,
+        from_hex,
+        keyword,
+        language,
+        every,
+        crc,
+        backup: BackupArgs { backup },
     } = args;
 
-    let png_file = fs::read(file_path.clone())?;
+    let png_file = read_input(&file_path)?;
+
+    let mut png = parse_png(&png_file)?;
+    let mut plan = vec![format!(
+        "parse {}: {} chunks",
+        file_path.display(),
+        png.chunks().len()
+    )];
+
+    if let Some(placeholder) = only_if_present {
+        if png.chunk_by_chunk_type(&placeholder).is_none() {
+            return Err(PngError::ChunkNotFound(placeholder.to_string()))
+                .context(format!("Placeholder chunk {} not found", placeholder));
+        }
+    }
+
+    let international = &chunk_type.bytes() == b"iTXt";
+    if language.is_some() && !international {
+        bail!("--language only applies to iTXt chunks");
+    }
+    let mut datas = Vec::new();
+    for message in message.into_iter().chain(messages) {
+        let message = match from_hex {
+            true => encoding::hex_decode(message.trim())?,
+            #[cfg(feature = "base64")]
+            false if base64 => encoding::base64_decode(&message)?,
+            false => message.into_bytes(),
+        };
+        let message = match (&keyword, international) {
+            (Some(keyword), true) => InternationalText::new(
+                keyword,
+                language.as_deref().unwrap_or_default(),
+                "",
+                &String::from_utf8(message).context("iTXt text must be valid UTF-8")?,
+            )?
+            .to_bytes(),
+            (Some(keyword), false) => text_payload(keyword, &message)?,
+            (None, _) => message,
+        };
+        #[cfg(feature = "compress")]
+        let message = match compress {
+            true => compress::compress(&message),
+            false => message,
+        };
+        check_message_size(
+            message.len(),
+            max_message_bytes.unwrap_or(DEFAULT_MAX_MESSAGE_BYTES),
+        )?;
+        datas.push(message);
+    }
+    if datas.is_empty() {
+        bail!("No message given");
+    }
+    let existing = png.chunk_by_chunk_type(&chunk_type).is_some();
+    if existing && !replace_if_changed {
+        warn(&[
+            ("kind", "duplicate-type"),
+            ("chunk_type", &chunk_type.to_string()),
+        ]);
+    }
+    if chunk_type.is_critical() {
+        warn(&[
+            ("kind", "critical-type"),
+            ("chunk_type", &chunk_type.to_string()),
+        ]);
+    }
+    if let Some(crc) = crc {
+        warn(&[
+            ("kind", "forced-crc"),
+            ("chunk_type", &chunk_type.to_string()),
+            ("crc", &format!("{:#010x}", crc)),
+        ]);
+    }
+    let new_chunk = |data: Vec<u8>| match crc {
+        Some(crc) => Chunk::new_unchecked(chunk_type, data, crc),
+        None => Chunk::try_new(chunk_type, data),
+    };
 
-    let mut png = Png::try_from(png_file.as_slice())?;
+    if let Some(every) = every {
+        if every == 0 {
+            bail!("--every must be at least 1");
+        }
+        if datas.len() > 1 {
+            bail!("--every takes a single message");
+        }
+        let data = datas.remove(0);
+        let ancillary: Vec<usize> = png
+            .chunks()
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| chunk.is_ancillary())
+            .map(|(index, _)| index)
+            .collect();
+        // Copy k follows the (k * every)-th ancillary chunk, but only where another ancillary
+        // chunk comes next, so no copy lands after IHDR or inside a run of IDAT chunks
+        let gaps: Vec<usize> = ancillary
+            .iter()
+            .skip(every - 1)
+            .step_by(every)
+            .map(|&index| index + 1)
+            .filter(|&index| png.chunks().get(index).is_some_and(Chunk::is_ancillary))
+            .collect();
+        if gaps.is_empty() {
+            bail!(
+                "--every {} leaves no room among {} ancillary chunks",
+                every,
+                ancillary.len()
+            );
+        }
+        let ordered = png.validate_order().is_ok();
+        for (copies, gap) in gaps.into_iter().enumerate() {
+            let index = gap + copies;
+            png.insert_chunk(index, new_chunk(data.clone())?)?;
+            plan.push(format!("insert chunk {} at index {}", chunk_type, index));
+        }
+        if ordered {
+            png.validate_order()
+                .context("--every would break the chunk order")?;
+        }
+    }
 
-    let chunk = Chunk::new(chunk_type.clone(), message.as_bytes().to_vec());
+    // Index of the first new chunk, or None to append each chunk before IEND
+    let start = match (before, after) {
+        _ if replace_if_changed && existing => {
+            if datas.len() > 1 {
+                bail!("--replace-if-changed takes a single message");
+            }
+            let index = position_of(&png, &chunk_type)?;
+            png.replace_chunk(&chunk_type.to_string(), datas.remove(0))?;
+            plan.push(format!("replace chunk {} at index {}", chunk_type, index));
+            None
+        }
+        // Clap rejects --before together with --after or --replace-if-changed
+        (Some(reference), _) => Some(position_of(&png, &reference)?),
+        (None, Some(reference)) => Some(position_of(&png, &reference)? + 1),
+        (None, None) => None,
+    };
+    for (i, data) in datas.into_iter().enumerate() {
+        let chunk = new_chunk(data)?;
+        let index = match start {
+            Some(start) => {
+                png.insert_chunk(start + i, chunk)?;
+                start + i
+            }
+            None => png.append_chunk(chunk),
+        };
+        plan.push(format!("insert chunk {} at index {}", chunk_type, index));
+    }
 
-    png.append_chunk(chunk);
+    if provenance {
+        let data = format!("pngme {}", env!("CARGO_PKG_VERSION")).into_bytes();
+        match png.chunk_by_type(PROVENANCE_CHUNK) {
+            Some(_) => png.replace_chunk(PROVENANCE_CHUNK, data)?,
+            None => {
+                png.append_chunk(Chunk::try_new(
+                    ChunkType::from_str(PROVENANCE_CHUNK)?,
+                    data,
+                )?);
+            }
+        }
+        plan.push(format!("stamp provenance chunk {}", PROVENANCE_CHUNK));
+    }
+    if stamp_time {
+        let time = ModificationTime::now();
+        // tIME may appear only once, so a new stamp replaces the old one
+        match png.chunk_by_type("tIME") {
+            Some(_) => png.replace_chunk("tIME", time.to_bytes().to_vec())?,
+            None => {
+                png.append_chunk(Chunk::try_new(
+                    ChunkType::new_unchecked(*b"tIME"),
+                    time.to_bytes().to_vec(),
+                )?);
+            }
+        }
+        plan.push(format!("stamp tIME chunk with {}", time));
+    }
 
-    let outputdir = match output_file {
+    let outputdir = match output.or(output_file) {
         Some(path) => path,
         None => file_path.clone(),
     };
-    fs::write(outputdir, png.as_bytes())?;
+    let bytes = png.as_bytes();
+    if replace_if_changed && fs::read(&outputdir).ok().as_ref() == Some(&bytes) {
+        if !global.quiet {
+            writeln!(out, "unchanged")?;
+        }
+        return Ok(());
+    }
+    write_png(
+        &outputdir,
+        &png,
+        bytes,
+        plan,
+        backup.as_deref(),
+        global,
+        out,
+    )
+}
+
+/// Rejects chunk data over the given limit, and always over `u32::MAX` bytes
+/// since the chunk length field could not store it
+fn check_message_size(len: usize, max: usize) -> Result<()> {
+    if len > u32::MAX as usize {
+        bail!(
+            "Message is {} bytes, but a chunk holds at most {} bytes",
+            len,
+            u32::MAX
+        );
+    }
+    if len > max {
+        bail!(
+            "Message is {} bytes, exceeding the maximum of {} bytes",
+            len,
+            max
+        );
+    }
+    Ok(())
+}
+
+/// Writes the serialized PNG, or with `--dry-run` skips the write and reports
+/// what would have been written. Combined with `--verbose` it prints every planned step
+fn write_png<W: Write>(
+    path: &Path,
+    png: &Png,
+    bytes: Vec<u8>,
+    mut plan: Vec<String>,
+    backup: Option<&str>,
+    global: &GlobalArgs,
+    out: &mut W,
+) -> Result<()> {
+    if is_stdin(path) {
+        bail!("Cannot write the result back to stdin, give an output path");
+    }
+    // Only an existing file is backed up, a new output path has nothing to lose
+    let backup = backup
+        .filter(|_| path.exists())
+        .map(|suffix| backup_path(path, suffix));
+    // Checked before the dry run so it reports exactly the writes a real run would make
+    match &backup {
+        Some(backup) if backup.exists() && !global.force => bail!(
+            "Backup {} already exists, pass --force to replace it",
+            backup.display()
+        ),
+        None if path.exists() && !global.force => bail!(
+            "Refusing to overwrite {}, pass --force, --backup or give a new output path",
+            path.display()
+        ),
+        _ => {}
+    }
+    if !global.dry_run {
+        if let Some(backup) = &backup {
+            fs::copy(path, backup)?;
+        }
+        fs::write(path, bytes)?;
+        return Ok(());
+    }
+    if global.verbose {
+        plan.push(format!(
+            "recompute totals: {} chunks, {} bytes",
+            png.chunk_count(),
+            png.total_size()
+        ));
+        if let Some(backup) = &backup {
+            plan.push(format!(
+                "would back up {} to {}",
+                path.display(),
+                backup.display()
+            ));
+        }
+        plan.push(format!("would write to {}", path.display()));
+        for (i, step) in plan.iter().enumerate() {
+            writeln!(out, "{}. {}", i + 1, step)?;
+        }
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "Dry run: would write {} chunks ({} bytes) to {}",
+        png.chunk_count(),
+        png.total_size(),
+        path.display()
+    )?;
+    Ok(())
+}
+
+/// The backup location of a file: its path with the suffix appended, e.g. `cat.png.bak`
+fn backup_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(suffix);
+    PathBuf::from(backup)
+}
+
+/// Checks the producer version stored in the provenance chunk against a minimum
+pub fn check_version(args: CheckVersionArgs, global: &GlobalArgs) -> Result<()> {
+    let CheckVersionArgs {
+        file_path,
+        min_version,
+    } = args;
+    let png = read_png(&file_path, global)?;
+    let stored = match png.chunk_by_type(PROVENANCE_CHUNK) {
+        Some(chunk) => chunk.data_as_string()?,
+        None => bail!("No provenance chunk found"),
+    };
+    let version = match stored.strip_prefix("pngme ") {
+        Some(version) => version,
+        None => bail!("Unrecognised provenance {:?}", stored),
+    };
+    if parse_version(version)? < parse_version(&min_version)? {
+        bail!(
+            "File was produced by pngme {}, older than {}",
+            version,
+            min_version
+        );
+    }
+    println!("File was produced by pngme {}", version);
+    Ok(())
+}
+
+/// Checks the CRC of every chunk and the IHDR/IEND placement of a PNG file
+pub fn validate(args: ValidateArgs, global: &GlobalArgs) -> Result<()> {
+    validate_to(args, global, &mut io::stdout().lock())
+}
+
+fn validate_to<W: Write>(args: ValidateArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
+    let ValidateArgs {
+        file_path,
+        verify,
+        is_png,
+    } = args;
+    if is_png {
+        let signature_ok = match is_stdin(&file_path) {
+            true => Png::is_png_reader(io::stdin().lock())?,
+            false => Png::is_png_reader(fs::File::open(&file_path)?)?,
+        };
+        if !signature_ok {
+            bail!(PngError::InvalidSignature);
+        }
+        if !global.quiet {
+            writeln!(out, "{} is a png", file_path.display())?;
+        }
+        return Ok(());
+    }
+    // Keep the stored CRCs so each one can be checked and reported
+    let png = Png::try_from_trusted(&read_input(&file_path)?)?;
+    warn_trailing_data(&png);
+    let report = png.verify_crcs();
+    if verify {
+        for (index, chunk_type, valid) in &report {
+            let status = match valid {
+                true => "pass",
+                false => "fail",
+            };
+            writeln!(out, "{}: {} {}", index, chunk_type, status)?;
+        }
+    }
+    let failed = report.iter().filter(|&&(_, _, valid)| !valid).count();
+    if failed > 0 {
+        bail!("{} of {} chunks have an invalid crc", failed, report.len());
+    }
+    png.validate_order()?;
+    if !global.quiet {
+        writeln!(out, "{} is valid", file_path.display())?;
+    }
     Ok(())
 }
 
+/// Parses a `major.minor.patch` version into a comparable tuple
+fn parse_version(version: &str) -> Result<(u64, u64, u64)> {
+    let parts: Vec<&str> = version.trim().split('.').collect();
+    match parts.as_slice() {
+        [major, minor, patch] => Ok((major.parse()?, minor.parse()?, patch.parse()?)),
+        _ => bail!("Invalid version {:?}, expected major.minor.patch", version),
+    }
+}
+
+/// Prints an advisory message to stderr as a `warning: key=value ...` line,
+/// keeping stdout free for data
+fn warn(fields: &[(&str, &str)]) {
+    eprintln!("{}", warning_line(fields));
+}
+
+fn warning_line(fields: &[(&str, &str)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| match value.contains(char::is_whitespace) {
+            true => format!("{}={:?}", key, value),
+            false => format!("{}={}", key, value),
+        })
+        .collect();
+    format!("warning: {}", fields.join(" "))
+}
+
+/// Returns true for the `-` path, which reads the PNG from stdin
+fn is_stdin(file_path: &Path) -> bool {
+    file_path == Path::new("-")
+}
+
+/// Reads the input file, or all of stdin when the path is `-`
+fn read_input(file_path: &Path) -> Result<Vec<u8>> {
+    read_input_from(file_path, io::stdin().lock())
+}
+
+fn read_input_from<R: io::Read>(file_path: &Path, mut stdin: R) -> Result<Vec<u8>> {
+    match is_stdin(file_path) {
+        true => {
+            let mut bytes = Vec::new();
+            stdin.read_to_end(&mut bytes)?;
+            Ok(bytes)
+        }
+        false => Ok(fs::read(file_path)?),
+    }
+}
+
+/// Reads a PNG for a read-only command, skipping CRC checks when `--fast` is set
+fn read_png(file_path: &Path, global: &GlobalArgs) -> Result<Png> {
+    let png_file = read_input(file_path)?;
+    let png = match global.fast {
+        true => Png::try_from_trusted(png_file.as_slice())?,
+        false => Png::try_from(png_file.as_slice())?,
+    };
+    warn_trailing_data(&png);
+    Ok(png)
+}
+
+/// Parses a PNG for a command that rewrites it, which carries any trailing data along
+fn parse_png(png_file: &[u8]) -> Result<Png> {
+    let png = Png::try_from(png_file)?;
+    warn_trailing_data(&png);
+    Ok(png)
+}
+
+/// Warns about bytes after IEND, which viewers ignore and could hide a payload
+fn warn_trailing_data(png: &Png) {
+    let trailing = png.trailing_data();
+    if !trailing.is_empty() {
+        warn(&[
+            ("kind", "trailing-data"),
+            ("bytes", &trailing.len().to_string()),
+        ]);
+    }
+}
+
+/// Finds the index of the first chunk of the given type
+fn position_of(png: &Png, chunk_type: &ChunkType) -> Result<usize> {
+    match png.position_of(&chunk_type.to_string()) {
+        Some(index) => Ok(index),
+        None => Err(PngError::ChunkNotFound(chunk_type.to_string()))
+            .context(format!("Reference chunk {} not found", chunk_type)),
+    }
+}
+
 /// Searches for a message hidden in a PNG file and prints the message if one is found
-pub fn decode(args: DecodeArgs) -> Result<()> {
+pub fn decode(args: DecodeArgs, global: &GlobalArgs) -> Result<()> {
+    decode_to(args, global, &mut io::stdout().lock())
+}
+
+fn decode_to<W: Write>(args: DecodeArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
     let DecodeArgs {
         file_path,
         chunk_type,
+        #[cfg(feature = "base64")]
+        base64,
+        hex,
+        raw,
+        keyword,
+        pattern,
+        all,
+        print0,
     } = args;
-    let png_file = fs::read(file_path)?;
-    let png = Png::try_from(png_file.as_slice())?;
-    match png.chunk_by_type(&chunk_type.to_string()) {
-        Some(chunk) => {
-            println!("The chunk is: {}", chunk.to_string());
-            Ok(())
+    let png = read_png(&file_path, global)?;
+    let wanted = match (&chunk_type, &pattern) {
+        (Some(chunk_type), _) => chunk_type.to_string(),
+        (None, pattern) => pattern.clone().unwrap_or_default(),
+    };
+    let mut matching = png
+        .chunks()
+        .iter()
+        .filter(|chunk| selects(chunk, chunk_type.as_ref(), pattern.as_deref()));
+    let found: Vec<Vec<u8>> = match keyword {
+        Some(keyword) => match matching.find_map(|chunk| text_value(chunk, &keyword)) {
+            Some(found) => vec![found],
+            None => {
+                return Err(PngError::ChunkNotFound(keyword.clone()))
+                    .context(format!("No matching chunk with keyword {:?}", keyword))
+            }
+        },
+        // A pattern or --all decodes every match, an exact type only the first
+        None => {
+            let chunks: Vec<&Chunk> = match pattern.is_some() || all {
+                true => matching.collect(),
+                false => matching.take(1).collect(),
+            };
+            if chunks.is_empty() {
+                bail!(PngError::ChunkNotFound(wanted));
+            }
+            let mut found = Vec::with_capacity(chunks.len());
+            for chunk in chunks {
+                // Plain data that merely starts with the magic prefix is not a zlib
+                // stream, so it is decoded as is
+                #[cfg(feature = "compress")]
+                if let Ok(data) = compress::decompress(chunk.data()) {
+                    found.push(data);
+                    continue;
+                }
+                found.push(chunk.data().to_vec());
+            }
+            found
+        }
+    };
+    let terminator = match print0 {
+        true => '\0',
+        false => '\n',
+    };
+    // Inflated data can outgrow what a chunk holds, so it is formatted without building one
+    for data in found {
+        match (hex, raw, global.quiet) {
+            #[cfg(feature = "base64")]
+            _ if base64 => write!(out, "{}", encoding::base64_encode(&data))?,
+            (true, _, _) => write!(out, "{}", encoding::hex_encode(&data))?,
+            (false, true, _) => match std::str::from_utf8(&data) {
+                Ok(message) => write!(out, "{}", message)?,
+                Err(_) => bail!("Unable to convert from vec<u8> to utf8"),
+            },
+            (false, false, true) => write!(out, "{}", String::from_utf8_lossy(&data))?,
+            // Matches the Display of a chunk: plain text or a binary placeholder
+            (false, false, false) => match std::str::from_utf8(&data) {
+                Ok(message) => write!(out, "The chunk is: {}", message)?,
+                Err(_) => write!(out, "The chunk is: <binary {} bytes>", data.len())?,
+            },
         }
-        None => bail!("Not found"),
+        write!(out, "{}", terminator)?;
+    }
+    Ok(())
+}
+
+/// Returns true if the chunk has the exact type, or matches the `?` wildcard pattern
+fn selects(chunk: &Chunk, chunk_type: Option<&ChunkType>, pattern: Option<&str>) -> bool {
+    match (chunk_type, pattern) {
+        (Some(chunk_type), _) => chunk.chunk_type() == chunk_type,
+        (None, Some(pattern)) => chunk.chunk_type().matches_pattern(pattern),
+        (None, None) => false,
+    }
+}
+
+/// Builds a tEXt payload: a 1 to 79 byte keyword, a null separator, then the text
+fn text_payload(keyword: &str, text: &[u8]) -> Result<Vec<u8>> {
+    text::check_keyword(keyword)?;
+    let mut payload = Vec::with_capacity(keyword.len() + 1 + text.len());
+    payload.extend_from_slice(keyword.as_bytes());
+    payload.push(0);
+    payload.extend_from_slice(text);
+    Ok(payload)
+}
+
+/// Returns the text of a chunk if its keyword matches, reading iTXt chunks in the
+/// international text layout and any other chunk as a tEXt payload
+fn text_value(chunk: &Chunk, keyword: &str) -> Option<Vec<u8>> {
+    let payload = chunk.data();
+    if &chunk.type_bytes() == b"iTXt" {
+        return InternationalText::try_from(payload)
+            .ok()
+            .filter(|text| text.keyword() == keyword)
+            .map(|text| text.text().as_bytes().to_vec());
+    }
+    let separator = payload.iter().position(|&byte| byte == 0)?;
+    match &payload[..separator] == keyword.as_bytes() {
+        true => Some(payload[separator + 1..].to_vec()),
+        false => None,
     }
 }
 
 /// Removes a chunk from a PNG file and saves the result
-pub fn remove(args: RemoveArgs) -> Result<()> {
+pub fn remove(args: RemoveArgs, global: &GlobalArgs) -> Result<()> {
+    remove_to(args, global, &mut io::stdout().lock())
+}
+
+fn remove_to<W: Write>(args: RemoveArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
     let RemoveArgs {
         file_path,
         chunk_type,
+        all,
+        index,
+        output_file,
+        pattern,
+        backup: BackupArgs { backup },
+    } = args;
+    let png_file = read_input(&file_path)?;
+    let mut png = parse_png(&png_file)?;
+    let mut plan = vec![format!(
+        "parse {}: {} chunks",
+        file_path.display(),
+        png.chunks().len()
+    )];
+    let output_path = output_file.unwrap_or_else(|| file_path.clone());
+    if let Some(pattern) = pattern {
+        let total = png.chunk_count();
+        png.retain_chunks(|chunk| !chunk.chunk_type().matches_pattern(&pattern));
+        let removed = total - png.chunk_count();
+        if removed == 0 {
+            bail!(PngError::ChunkNotFound(pattern));
+        }
+        plan.push(format!("remove {} chunks matching {}", removed, pattern));
+        if !global.quiet {
+            writeln!(out, "Removed {} chunks", removed)?;
+        }
+        let bytes = png.as_bytes();
+        return write_png(
+            &output_path,
+            &png,
+            bytes,
+            plan,
+            backup.as_deref(),
+            global,
+            out,
+        );
+    }
+    let chunk_type = match (index, chunk_type) {
+        (Some(index), expected) => {
+            if let (Some(expected), Some(chunk)) = (expected, png.chunks().get(index)) {
+                if chunk.chunk_type() != &expected {
+                    bail!(
+                        "Chunk at index {} is {}, not {}",
+                        index,
+                        chunk.chunk_type(),
+                        expected
+                    );
+                }
+            }
+            let removed = png.remove_chunk_at(index)?;
+            plan.push(format!(
+                "remove chunk {} at index {}",
+                removed.chunk_type(),
+                index
+            ));
+            let bytes = png.as_bytes();
+            return write_png(
+                &output_path,
+                &png,
+                bytes,
+                plan,
+                backup.as_deref(),
+                global,
+                out,
+            );
+        }
+        (None, Some(chunk_type)) => chunk_type,
+        (None, None) => bail!("Either a chunk type or --index is required"),
+    };
+    match all {
+        true => {
+            let removed = png.remove_chunks(&chunk_type.to_string());
+            if removed == 0 {
+                bail!(PngError::ChunkNotFound(chunk_type.to_string()));
+            }
+            plan.push(format!("remove {} chunks of type {}", removed, chunk_type));
+            if !global.quiet {
+                writeln!(out, "Removed {} chunks", removed)?;
+            }
+        }
+        false => {
+            let index = position_of(&png, &chunk_type)?;
+            png.remove_chunk_by_chunk_type(&chunk_type)?;
+            plan.push(format!("remove chunk {} at index {}", chunk_type, index));
+        }
+    }
+
+    let bytes = png.as_bytes();
+    write_png(
+        &output_path,
+        &png,
+        bytes,
+        plan,
+        backup.as_deref(),
+        global,
+        out,
+    )
+}
+
+/// Replaces the message of the first matching chunk in place and saves the result
+pub fn replace(args: ReplaceArgs, global: &GlobalArgs) -> Result<()> {
+    replace_to(args, global, &mut io::stdout().lock())
+}
+
+fn replace_to<W: Write>(args: ReplaceArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
+    let ReplaceArgs {
+        file_path,
+        chunk_type,
+        message,
+        backup: BackupArgs { backup },
+    } = args;
+    let png_file = read_input(&file_path)?;
+    let mut png = parse_png(&png_file)?;
+    let mut plan = vec![format!(
+        "parse {}: {} chunks",
+        file_path.display(),
+        png.chunks().len()
+    )];
+    let index = position_of(&png, &chunk_type)?;
+    png.replace_chunk(&chunk_type.to_string(), message.into_bytes())?;
+    plan.push(format!("replace chunk {} at index {}", chunk_type, index));
+
+    let bytes = png.as_bytes();
+    write_png(
+        &file_path,
+        &png,
+        bytes,
+        plan,
+        backup.as_deref(),
+        global,
+        out,
+    )
+}
+
+/// Changes the type of the first matching chunk in place and saves the result
+pub fn rename(args: RenameArgs, global: &GlobalArgs) -> Result<()> {
+    rename_to(args, global, &mut io::stdout().lock())
+}
+
+fn rename_to<W: Write>(args: RenameArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
+    let RenameArgs {
+        file_path,
+        from,
+        to,
+    } = args;
+    let png_file = read_input(&file_path)?;
+    let mut png = parse_png(&png_file)?;
+    let mut plan = vec![format!(
+        "parse {}: {} chunks",
+        file_path.display(),
+        png.chunks().len()
+    )];
+    let index = position_of(&png, &from)?;
+    png.chunks_mut()[index].set_chunk_type(to);
+    plan.push(format!(
+        "rename chunk {} at index {} to {}",
+        from, index, to
+    ));
+
+    let bytes = png.as_bytes();
+    write_png(&file_path, &png, bytes, plan, None, global, out)
+}
+
+/// Copies the first matching chunk of the source PNG into the destination PNG, before IEND
+pub fn append_from(args: AppendFromArgs, global: &GlobalArgs) -> Result<()> {
+    let AppendFromArgs {
+        source,
+        dest,
+        chunk_type,
+    } = args;
+    let source_png = read_png(&source, global)?;
+    let dest_file = read_input(&dest)?;
+    let mut dest_png = parse_png(&dest_file)?;
+    let mut plan = vec![format!(
+        "parse {}: {} chunks",
+        dest.display(),
+        dest_png.chunks().len()
+    )];
+    let index = copy_chunk(&source_png, &mut dest_png, &chunk_type)?;
+    plan.push(format!(
+        "insert chunk {} from {} at index {}",
+        chunk_type,
+        source.display(),
+        index
+    ));
+
+    let bytes = dest_png.as_bytes();
+    write_png(
+        &dest,
+        &dest_png,
+        bytes,
+        plan,
+        None,
+        global,
+        &mut io::stdout().lock(),
+    )
+}
+
+/// Appends a copy of the first chunk of the given type in `source` to `dest`,
+/// returning the index it was inserted at
+fn copy_chunk(source: &Png, dest: &mut Png, chunk_type: &ChunkType) -> Result<usize> {
+    match source.chunk_by_chunk_type(chunk_type) {
+        Some(chunk) => Ok(dest.append_chunk(chunk.clone())),
+        None => Err(PngError::ChunkNotFound(chunk_type.to_string()))
+            .context(format!("Chunk {} not found in source", chunk_type)),
+    }
+}
+
+/// Removes duplicate ancillary chunks from a PNG file and saves the result
+pub fn dedup(args: DedupArgs, global: &GlobalArgs) -> Result<()> {
+    dedup_to(args, global, &mut io::stdout().lock())
+}
+
+fn dedup_to<W: Write>(args: DedupArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
+    let DedupArgs {
+        file_path,
+        chunk_type,
     } = args;
-    let png_file = fs::read(file_path.clone())?;
-    let mut png = Png::try_from(png_file.as_slice())?;
-    png.remove_chunk(&chunk_type.to_string())?;
+    let png_file = read_input(&file_path)?;
+    let mut png = parse_png(&png_file)?;
+    let mut plan = vec![format!(
+        "parse {}: {} chunks",
+        file_path.display(),
+        png.chunk_count()
+    )];
+    let removed = png.dedup_chunks(chunk_type.as_ref());
+    plan.push(format!("remove {} duplicate chunks", removed));
+    if !global.quiet {
+        writeln!(out, "Removed {} duplicate chunks", removed)?;
+    }
+
+    let bytes = png.as_bytes();
+    write_png(&file_path, &png, bytes, plan, None, global, out)
+}
 
-    fs::write(file_path, png.as_bytes())?;
+/// Applies one operation to every png file in a directory, prefixing each line of
+/// output with the file it belongs to. Failures are reported and the rest still run
+pub fn batch(args: BatchArgs, global: &GlobalArgs) -> Result<()> {
+    batch_to(args, global, &mut io::stdout().lock())
+}
+
+fn batch_to<W: Write>(args: BatchArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
+    if args.operation == BatchOperation::Remove && args.chunk_type.is_none() {
+        bail!("remove needs a chunk type, pass --type");
+    }
+    let files = png_files(&args.dir, args.recursive)?;
+    let jobs = match args.jobs {
+        0 => std::thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+        jobs => jobs,
+    }
+    .clamp(1, files.len().max(1));
+    // Drawn on stderr so stdout stays clean, and only for a terminal someone is watching
+    let progress = cfg!(feature = "progress") && !global.quiet && io::stderr().is_terminal();
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    // Workers take the next unclaimed file, results are put back in file order below
+    // so the report does not depend on which thread finished first
+    let mut results: Vec<(usize, Vec<u8>, Result<()>)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file_path) = files.get(index) else {
+                            break;
+                        };
+                        let mut buffer = Vec::new();
+                        let result = batch_file(&args, file_path, global, &mut buffer);
+                        results.push((index, buffer, result));
+                        if progress {
+                            let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                            eprint!("\r\x1b[K{}", progress_line(done, files.len()));
+                        }
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("batch worker panicked"))
+            .collect()
+    });
+    results.sort_by_key(|(index, _, _)| *index);
+    if progress {
+        eprint!("\r\x1b[K");
+    }
+
+    let mut failed = 0;
+    for (index, buffer, result) in results {
+        let file_path = &files[index];
+        for line in String::from_utf8_lossy(&buffer).lines() {
+            writeln!(out, "{}: {}", file_path.display(), line)?;
+        }
+        if let Err(error) = result {
+            failed += 1;
+            writeln!(out, "{}: error: {}", file_path.display(), error)?;
+        }
+    }
+    if !global.quiet {
+        writeln!(
+            out,
+            "{} of {} files succeeded",
+            files.len() - failed,
+            files.len()
+        )?;
+    }
+    if failed > 0 {
+        bail!("{} of {} files failed", failed, files.len());
+    }
     Ok(())
 }
 
+/// Applies the batch operation to one file, writing its report into the buffer
+fn batch_file(
+    args: &BatchArgs,
+    file_path: &Path,
+    global: &GlobalArgs,
+    buffer: &mut Vec<u8>,
+) -> Result<()> {
+    match (args.operation, args.chunk_type) {
+        (BatchOperation::Validate, _) => validate_to(
+            ValidateArgs {
+                file_path: file_path.to_path_buf(),
+                verify: false,
+                is_png: false,
+            },
+            global,
+            buffer,
+        ),
+        (BatchOperation::Dedup, chunk_type) => dedup_to(
+            DedupArgs {
+                file_path: file_path.to_path_buf(),
+                chunk_type,
+            },
+            global,
+            buffer,
+        ),
+        (BatchOperation::Remove, Some(chunk_type)) => remove_to(
+            RemoveArgs {
+                file_path: file_path.to_path_buf(),
+                chunk_type: Some(chunk_type),
+                pattern: None,
+                all: true,
+                index: None,
+                output_file: None,
+                backup: BackupArgs::default(),
+            },
+            global,
+            buffer,
+        ),
+        (BatchOperation::Remove, None) => bail!("remove needs a chunk type, pass --type"),
+    }
+}
+
+/// Renders a 20 column progress bar followed by the processed and total file counts
+fn progress_line(done: usize, total: usize) -> String {
+    const WIDTH: usize = 20;
+    let filled = match total {
+        0 => WIDTH,
+        _ => done * WIDTH / total,
+    };
+    format!(
+        "[{}{}] {}/{} files",
+        "#".repeat(filled),
+        "-".repeat(WIDTH - filled),
+        done,
+        total
+    )
+}
+
+/// Collects the `*.png` files in a directory in sorted order, descending into
+/// subdirectories when recursive. Files without a PNG signature are skipped with a warning
+fn png_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<PathBuf>>>()?;
+    entries.sort();
+    let mut files = Vec::new();
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                files.extend(png_files(&path, recursive)?);
+            }
+            continue;
+        }
+        let is_png_name = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        if !is_png_name {
+            continue;
+        }
+        let has_signature = fs::File::open(&path)
+            .map_err(Into::into)
+            .and_then(Png::is_png_reader)
+            .unwrap_or(false);
+        match has_signature {
+            true => files.push(path),
+            false => warn(&[
+                ("skipped", &path.display().to_string()),
+                ("reason", "not a png file"),
+            ]),
+        }
+    }
+    Ok(files)
+}
+
 /// Prints all of the chunks in a PNG file
-pub fn print_chunks(args: PrintArgs) -> Result<()> {
-    let PrintArgs { file_path } = args;
-    let png_file = fs::read(file_path.clone())?;
-    let png = Png::try_from(png_file.as_slice())?;
+pub fn print_chunks(args: PrintArgs, global: &GlobalArgs) -> Result<()> {
+    let png = read_png(&args.file_path, global)?;
+    let color = use_color(global.color, io::stdout().is_terminal());
+    write_chunks(&png, &args, global, color, &mut io::stdout().lock())
+}
+
+/// Decides whether to emit ANSI colors, `auto` requires a terminal and no NO_COLOR
+fn use_color(choice: ColorChoice, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            is_terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        }
+    }
+}
+
+/// Colors the chunk type: red for a bad CRC, bold blue for critical, green for ancillary
+fn paint_chunk_type(chunk: &Chunk, color: bool) -> String {
+    if !color {
+        return chunk.type_str();
+    }
+    let code = match (chunk.crc_is_valid(), chunk.is_critical()) {
+        (false, _) => "31",
+        (true, true) => "1;34",
+        (true, false) => "32",
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, chunk.chunk_type())
+}
+
+/// Writes the `print` listing of a PNG to the given output
+fn write_chunks<W: Write>(
+    png: &Png,
+    args: &PrintArgs,
+    global: &GlobalArgs,
+    color: bool,
+    out: &mut W,
+) -> Result<()> {
     let mut count = 0;
+    let mut omitted = 0;
+    let mut next_offset = Png::STANDARD_HEADER.len();
 
     // Print all message in png
     for chunk in png.chunks() {
-        if let Ok(msg) = chunk.data_as_string() {
-            if msg.trim() != "" {
-                count += 1;
-                println!(
-                    "{}: Chunk Type - {}, Msg: {}",
+        let offset = next_offset;
+        next_offset += chunk.byte_len();
+        if !args.types.is_empty() && !args.types.contains(chunk.chunk_type()) {
+            continue;
+        }
+        let position = match args.offsets {
+            true => format!(", Offset: {}", offset),
+            false => String::new(),
+        };
+        let chunk_type = paint_chunk_type(chunk, color);
+        let bits = match args.emit_chunk_type_bits {
+            true => format!(", Bits: {}", type_bits(chunk.chunk_type())),
+            false => String::new(),
+        };
+        // Hex dumps show every chunk, messages skip blank and binary data unless --show-all
+        let msg = match args.hex {
+            true => None,
+            false => match (chunk_details(chunk), chunk.data_as_string()) {
+                (Some(details), _) => Some(format!(
+                    "<{}: {}>",
+                    chunk.chunk_type().description().unwrap_or_default(),
+                    details
+                )),
+                (None, Ok(msg)) if args.show_all || msg.trim() != "" => Some(msg),
+                // Mostly text with a few bad bytes is worth showing, pure binary is not
+                (None, Err(_)) => {
+                    let lossy = chunk.data_as_string_lossy();
+                    match (has_visible_text(&lossy), args.show_all) {
+                        (true, _) => Some(lossy),
+                        (false, true) => Some(format!("<binary {} bytes>", chunk.length())),
+                        (false, false) => continue,
+                    }
+                }
+                _ => continue,
+            },
+        };
+        if args.limit.is_some_and(|limit| count >= limit) {
+            omitted += 1;
+            continue;
+        }
+        count += 1;
+        let msg = match msg {
+            Some(msg) => msg,
+            None => {
+                writeln!(
+                    out,
+                    "{}: Chunk Type - {}{}{}, Length: {}",
                     count,
-                    chunk.chunk_type(),
-                    msg
-                )
+                    chunk_type,
+                    position,
+                    bits,
+                    chunk.length()
+                )?;
+                write!(out, "{}", hex_dump(chunk.data()))?;
+                continue;
             }
+        };
+        let layout = match args.offsets {
+            true => format!("{}, Length: {}", position, chunk.length()),
+            false => position,
+        };
+        let msg = match args.escape {
+            true => escape_control(&msg),
+            false => msg,
+        };
+        writeln!(
+            out,
+            "{}: Chunk Type - {}{}{}, Msg: {}",
+            count, chunk_type, layout, bits, msg
+        )?;
+    }
+    if omitted > 0 && !global.quiet {
+        writeln!(out, "{} more chunks omitted by --limit", omitted)?;
+    }
+    if args.show_trailing && !png.trailing_data().is_empty() {
+        let trailing = png.trailing_data();
+        writeln!(out, "Trailing data after IEND: {} bytes", trailing.len())?;
+        match std::str::from_utf8(trailing) {
+            Ok(text) if !args.hex => writeln!(out, "{}", text)?,
+            _ => write!(out, "{}", hex_dump(trailing))?,
+        }
+    }
+    if !global.quiet {
+        writeln!(out, "{} results in total", count)?;
+    }
+    Ok(())
+}
+
+/// Returns true if the text has a visible character other than the U+FFFD replacement
+fn has_visible_text(text: &str) -> bool {
+    text.chars()
+        .any(|c| c != char::REPLACEMENT_CHARACTER && !c.is_control() && !c.is_whitespace())
+}
+
+/// Prints every chunk whose UTF-8 message contains the query
+pub fn search(args: SearchArgs, global: &GlobalArgs) -> Result<()> {
+    let png = read_png(&args.file_path, global)?;
+    search_to(&png, &args, global, &mut io::stdout().lock())
+}
+
+fn search_to<W: Write>(
+    png: &Png,
+    args: &SearchArgs,
+    global: &GlobalArgs,
+    out: &mut W,
+) -> Result<()> {
+    let query = match args.ignore_case {
+        true => args.query.to_lowercase(),
+        false => args.query.clone(),
+    };
+    let mut matches = 0;
+    for chunk in png.chunks() {
+        let msg = match chunk.data_as_string() {
+            Ok(msg) => msg,
+            Err(_) => continue,
+        };
+        let found = match args.ignore_case {
+            true => msg.to_lowercase().contains(&query),
+            false => msg.contains(&query),
+        };
+        if found {
+            matches += 1;
+            writeln!(out, "{}: {}", chunk.chunk_type(), msg)?;
+        }
+    }
+    if !global.quiet {
+        writeln!(out, "{} matches in total", matches)?;
+    }
+    Ok(())
+}
+
+/// Prints each chunk with the description of its standard type, or its property bits if custom
+pub fn classify(args: ClassifyArgs, global: &GlobalArgs) -> Result<()> {
+    let png = read_png(&args.file_path, global)?;
+    write_classification(&png, &mut io::stdout().lock())
+}
+
+fn write_classification<W: Write>(png: &Png, out: &mut W) -> Result<()> {
+    for (i, chunk) in png.chunks().iter().enumerate() {
+        let chunk_type = chunk.chunk_type();
+        let label = match (chunk_type.description(), chunk_details(chunk)) {
+            (Some(description), Some(details)) => format!("{}, {}", description, details),
+            (Some(description), None) => description.to_string(),
+            (None, _) => format!(
+                "custom/unknown, {} {}",
+                match chunk_type.is_critical() {
+                    true => "critical",
+                    false => "ancillary",
+                },
+                match chunk_type.is_public() {
+                    true => "public",
+                    false => "private",
+                }
+            ),
+        };
+        writeln!(out, "{}: {} - {}", i, chunk_type, label)?;
+    }
+    Ok(())
+}
+
+/// Summarizes the binary chunks print knows how to read: the time in `tIME`, the frame
+/// and play counts of `acTL`, and the sequence number that starts `fcTL` and `fdAT`.
+/// Returns None for other or malformed chunks
+fn chunk_details(chunk: &Chunk) -> Option<String> {
+    let sequence = || -> Option<u32> {
+        let bytes = chunk.data().get(..4)?;
+        Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    match &chunk.type_bytes() {
+        b"tIME" => ModificationTime::try_from(chunk.data())
+            .ok()
+            .map(|time| time.to_string()),
+        b"acTL" => {
+            let control = AnimationControl::try_from(chunk).ok()?;
+            let plays = match control.num_plays {
+                0 => "loops forever".to_string(),
+                plays => format!("plays {} times", plays),
+            };
+            Some(format!("{} frames, {}", control.num_frames, plays))
+        }
+        b"fcTL" => Some(format!("sequence {}", sequence()?)),
+        b"fdAT" => Some(format!(
+            "sequence {}, {} bytes",
+            sequence()?,
+            chunk.data().len() - 4
+        )),
+        _ => None,
+    }
+}
+
+/// Reports the file size and how the chunk data splits between image data,
+/// other critical chunks, and standard and custom ancillary chunks
+pub fn stats(args: StatsArgs, global: &GlobalArgs) -> Result<()> {
+    let png = read_png(&args.file_path, global)?;
+    write_stats(&png, &mut io::stdout().lock())
+}
+
+fn write_stats<W: Write>(png: &Png, out: &mut W) -> Result<()> {
+    let (mut image_data, mut critical, mut standard, mut custom) = (0, 0, 0, 0);
+    for chunk in png.chunks() {
+        let length = chunk.length() as usize;
+        let chunk_type = chunk.chunk_type();
+        match (
+            &chunk_type.bytes() == b"IDAT",
+            chunk_type.is_critical(),
+            chunk_type.description().is_some(),
+        ) {
+            (true, _, _) => image_data += length,
+            (false, true, _) => critical += length,
+            (false, false, true) => standard += length,
+            (false, false, false) => custom += length,
+        }
+    }
+    let total = png.total_size();
+    let ancillary = standard + custom;
+    writeln!(out, "File size: {} bytes", total)?;
+    writeln!(out, "Image data: {} bytes", image_data)?;
+    writeln!(out, "Other critical data: {} bytes", critical)?;
+    writeln!(out, "Standard ancillary data: {} bytes", standard)?;
+    writeln!(out, "Custom ancillary data: {} bytes", custom)?;
+    writeln!(
+        out,
+        "Ancillary share: {:.1}% of the file, {:.1}% custom",
+        percentage(ancillary, total),
+        percentage(custom, total)
+    )?;
+    match image_data {
+        0 => writeln!(out, "No image data")?,
+        _ => writeln!(
+            out,
+            "Ancillary to image data ratio: {:.2}",
+            ancillary as f64 / image_data as f64
+        )?,
+    }
+    Ok(())
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => part as f64 * 100.0 / total as f64,
+    }
+}
+
+/// Groups shown by tree, in the order they are printed
+const TREE_CATEGORIES: [&str; 7] = [
+    "Header",
+    "Palette",
+    "Image data",
+    "Text",
+    "Other ancillary",
+    "Custom",
+    "End",
+];
+
+/// Prints the chunks under their category, each group with its chunk count
+pub fn tree(args: TreeArgs, global: &GlobalArgs) -> Result<()> {
+    let png = read_png(&args.file_path, global)?;
+    write_tree(
+        &png,
+        &args.file_path.display().to_string(),
+        &mut io::stdout().lock(),
+    )
+}
+
+fn write_tree<W: Write>(png: &Png, name: &str, out: &mut W) -> Result<()> {
+    writeln!(out, "{} ({} chunks)", name, png.chunk_count())?;
+    let groups: Vec<_> = TREE_CATEGORIES
+        .iter()
+        .map(|&category| {
+            let chunks: Vec<_> = png
+                .chunks()
+                .iter()
+                .enumerate()
+                .filter(|(_, chunk)| tree_category(chunk.chunk_type()) == category)
+                .collect();
+            (category, chunks)
+        })
+        .filter(|(_, chunks)| !chunks.is_empty())
+        .collect();
+    for (i, (category, chunks)) in groups.iter().enumerate() {
+        let last_group = i + 1 == groups.len();
+        let (branch, indent) = match last_group {
+            true => ("└── ", "    "),
+            false => ("├── ", "│   "),
+        };
+        writeln!(out, "{}{} ({})", branch, category, chunks.len())?;
+        for (j, (index, chunk)) in chunks.iter().enumerate() {
+            let leaf = match j + 1 == chunks.len() {
+                true => "└── ",
+                false => "├── ",
+            };
+            writeln!(
+                out,
+                "{}{}{}: {} {} bytes",
+                indent,
+                leaf,
+                index,
+                chunk.chunk_type(),
+                chunk.length()
+            )?;
         }
     }
-    println!("{} results in total", count);
     Ok(())
 }
+
+/// Explains the property bit carried by each letter of a chunk type code
+pub fn chunk_type_info(args: ChunkTypeInfoArgs) -> Result<()> {
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    write_chunk_type_info(&chunk_type, &mut io::stdout().lock())
+}
+
+fn write_chunk_type_info<W: Write>(chunk_type: &ChunkType, out: &mut W) -> Result<()> {
+    writeln!(
+        out,
+        "{}: {}",
+        chunk_type,
+        chunk_type.description().unwrap_or("custom/unknown")
+    )?;
+    let [critical, public, reserved, safe_to_copy] = chunk_type.bytes().map(char::from);
+    let lines = [
+        (
+            critical,
+            match chunk_type.is_critical() {
+                true => "critical, decoders must understand it to display the image",
+                false => "ancillary, decoders may ignore it",
+            },
+        ),
+        (
+            public,
+            match chunk_type.is_public() {
+                true => "public, part of the PNG specification or a registered extension",
+                false => "private, defined by an application",
+            },
+        ),
+        (
+            reserved,
+            match chunk_type.is_reserved_bit_valid() {
+                true => "reserved bit valid",
+                false => "reserved bit set, the type is invalid in a PNG file",
+            },
+        ),
+        (
+            safe_to_copy,
+            match chunk_type.is_safe_to_copy() {
+                true => "safe to copy when an editor changes the image data",
+                false => "unsafe to copy when an editor changes the image data",
+            },
+        ),
+    ];
+    for (letter, meaning) in lines {
+        writeln!(out, "  {}: {}", letter, meaning)?;
+    }
+    Ok(())
+}
+
+/// Picks the tree group of a chunk from its type, unknown types are custom
+fn tree_category(chunk_type: &ChunkType) -> &'static str {
+    match (&chunk_type.bytes(), chunk_type.description().is_some()) {
+        (b"IHDR", _) => "Header",
+        (b"PLTE", _) => "Palette",
+        (b"IDAT" | b"fdAT", _) => "Image data",
+        (b"tEXt" | b"zTXt" | b"iTXt", _) => "Text",
+        (b"IEND", _) => "End",
+        (_, true) => "Other ancillary",
+        (_, false) => "Custom",
+    }
+}
+
+/// Lists chunks only in the first file, only in the second, or in both with different data
+pub fn compare(args: CompareArgs, global: &GlobalArgs) -> Result<()> {
+    let first = read_png(&args.first, global)?;
+    let second = read_png(&args.second, global)?;
+    write_comparison(&first, &second, &mut io::stdout().lock())
+}
+
+/// Pairs chunks by type and occurrence, so the second tEXt of one file is
+/// compared with the second tEXt of the other
+fn write_comparison<W: Write>(first: &Png, second: &Png, out: &mut W) -> Result<()> {
+    let first = keyed_chunks(first);
+    let second = keyed_chunks(second);
+    let mut differences = 0;
+    for (key, chunk) in &first {
+        let (chunk_type, occurrence) = key;
+        match find_keyed(&second, key) {
+            None => writeln!(out, "- {} #{}", chunk_type, occurrence + 1)?,
+            Some(other) if other.length() != chunk.length() || other.crc() != chunk.crc() => {
+                writeln!(
+                    out,
+                    "~ {} #{}: length {} -> {}, crc {:08x} -> {:08x}",
+                    chunk_type,
+                    occurrence + 1,
+                    chunk.length(),
+                    other.length(),
+                    chunk.crc(),
+                    other.crc()
+                )?
+            }
+            Some(_) => continue,
+        }
+        differences += 1;
+    }
+    for (key, _) in &second {
+        if find_keyed(&first, key).is_none() {
+            writeln!(out, "+ {} #{}", key.0, key.1 + 1)?;
+            differences += 1;
+        }
+    }
+    match differences {
+        0 => writeln!(out, "identical chunk structure")?,
+        _ => writeln!(out, "{} differences", differences)?,
+    }
+    Ok(())
+}
+
+/// Chunk type and how many chunks of that type came before it
+type ChunkKey = (ChunkType, usize);
+
+fn keyed_chunks(png: &Png) -> Vec<(ChunkKey, &Chunk)> {
+    let mut seen: Vec<ChunkType> = Vec::new();
+    png.chunks()
+        .iter()
+        .map(|chunk| {
+            let occurrence = seen.iter().filter(|&t| t == chunk.chunk_type()).count();
+            seen.push(*chunk.chunk_type());
+            ((*chunk.chunk_type(), occurrence), chunk)
+        })
+        .collect()
+}
+
+fn find_keyed<'a>(chunks: &[(ChunkKey, &'a Chunk)], key: &ChunkKey) -> Option<&'a Chunk> {
+    chunks
+        .iter()
+        .find(|(other, _)| other == key)
+        .map(|&(_, chunk)| chunk)
+}
+
+/// Renders data as a classic dump of 16 bytes per line: offset, hex bytes and printable ASCII
+fn hex_dump(data: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, bytes) in data.chunks(16).enumerate() {
+        let hex: Vec<String> = (0..16)
+            .map(|i| match bytes.get(i) {
+                Some(byte) => format!("{:02x}", byte),
+                None => "  ".to_string(),
+            })
+            .collect();
+        let ascii: String = bytes
+            .iter()
+            .map(|&byte| match byte.is_ascii_graphic() || byte == b' ' {
+                true => byte as char,
+                false => '.',
+            })
+            .collect();
+        dump.push_str(&format!(
+            "{:08x}  {}  {}  |{}|\n",
+            line * 16,
+            hex[..8].join(" "),
+            hex[8..].join(" "),
+            ascii
+        ));
+    }
+    dump
+}
+
+/// Prints the number of chunks in a PNG file using the header-walking fast path
+pub fn count(args: CountArgs, global: &GlobalArgs) -> Result<()> {
+    let CountArgs { file_path, pattern } = args;
+    let count = match pattern {
+        Some(pattern) => count_matching(&read_png(&file_path, global)?, &pattern),
+        None if is_stdin(&file_path) => Png::count_chunks_fast(io::stdin().lock())?,
+        None => Png::count_chunks_fast(io::BufReader::new(fs::File::open(file_path)?))?,
+    };
+    println!("{}", count);
+    Ok(())
+}
+
+fn count_matching(png: &Png, pattern: &str) -> usize {
+    png.chunks()
+        .iter()
+        .filter(|chunk| chunk.chunk_type().matches_pattern(pattern))
+        .count()
+}
+
+/// Writes the data of every chunk to a file in the output directory named after the template
+pub fn extract(args: ExtractArgs, global: &GlobalArgs) -> Result<()> {
+    let ExtractArgs {
+        file_path,
+        output_dir,
+        name_template,
+    } = args;
+    let png = read_png(&file_path, global)?;
+    fs::create_dir_all(&output_dir)?;
+
+    let total = png.chunks().len();
+    for (i, chunk) in png.chunks().iter().enumerate() {
+        fs::write(
+            output_dir.join(extract_file_name(&name_template, i + 1, total, chunk)),
+            chunk.data(),
+        )?;
+    }
+    if !global.quiet {
+        println!("Extracted {} chunks", total);
+    }
+    Ok(())
+}
+
+/// Writes the data of every ancillary chunk to `<index>_<type>.bin` in the output
+/// directory, plus a manifest.json recording each chunk's index, type and file
+pub fn export(args: ExportArgs, global: &GlobalArgs) -> Result<()> {
+    let ExportArgs {
+        file_path,
+        output_dir,
+    } = args;
+    let png = read_png(&file_path, global)?;
+    let entries = export_chunks(&png, &output_dir)?;
+    fs::write(
+        output_dir.join(manifest::MANIFEST_FILE),
+        manifest::to_json(&file_path.display().to_string(), &entries),
+    )?;
+    if !global.quiet {
+        println!("Exported {} chunks", entries.len());
+    }
+    Ok(())
+}
+
+fn export_chunks(png: &Png, output_dir: &Path) -> Result<Vec<ManifestEntry>> {
+    fs::create_dir_all(output_dir)?;
+    let mut entries = Vec::new();
+    for (index, chunk) in png.chunks().iter().enumerate() {
+        if chunk.is_critical() {
+            continue;
+        }
+        let file = format!("{}_{}.bin", index, chunk.chunk_type());
+        fs::write(output_dir.join(&file), chunk.data())?;
+        entries.push(ManifestEntry {
+            index,
+            chunk_type: *chunk.chunk_type(),
+            file,
+            length: chunk.length(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Appends the chunks listed in an export manifest to a PNG, in manifest order
+pub fn import(args: ImportArgs, global: &GlobalArgs) -> Result<()> {
+    import_to(args, global, &mut io::stdout().lock())
+}
+
+fn import_to<W: Write>(args: ImportArgs, global: &GlobalArgs, out: &mut W) -> Result<()> {
+    let ImportArgs {
+        file_path,
+        input_dir,
+        output_file,
+    } = args;
+    let png_file = read_input(&file_path)?;
+    let mut png = parse_png(&png_file)?;
+    let mut plan = vec![format!(
+        "parse {}: {} chunks",
+        file_path.display(),
+        png.chunk_count()
+    )];
+    let entries = manifest::from_json(&fs::read_to_string(
+        input_dir.join(manifest::MANIFEST_FILE),
+    )?)?;
+    // Read every file before changing anything so a missing one leaves the PNG untouched
+    let mut chunks = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        // Only a bare file name, so a manifest cannot reach outside the input directory
+        let mut components = Path::new(&entry.file).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => {}
+            _ => bail!(
+                "Manifest file {:?} must be a plain file name inside the input directory",
+                entry.file
+            ),
+        }
+        let path = input_dir.join(&entry.file);
+        if !path.is_file() {
+            bail!("Manifest file {} does not exist", path.display());
+        }
+        chunks.push(Chunk::try_new(entry.chunk_type, fs::read(&path)?)?);
+    }
+    for chunk in chunks {
+        let chunk_type = *chunk.chunk_type();
+        let index = png.append_chunk(chunk);
+        plan.push(format!("insert chunk {} at index {}", chunk_type, index));
+    }
+    if !global.quiet {
+        writeln!(out, "Imported {} chunks", entries.len())?;
+    }
+
+    let output_path = output_file.unwrap_or_else(|| file_path.clone());
+    let bytes = png.as_bytes();
+    write_png(&output_path, &png, bytes, plan, None, global, out)
+}
+
+/// Fills in the name template placeholders. The index is zero-padded to at least
+/// 3 digits so filenames sort in chunk order, and the type is made filesystem safe
+fn extract_file_name(template: &str, index: usize, total: usize, chunk: &Chunk) -> String {
+    let width = total.to_string().len().max(3);
+    let chunk_type: String = chunk
+        .chunk_type()
+        .to_string()
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    template
+        .replace("{index}", &format!("{:0width$}", index, width = width))
+        .replace("{type}", &chunk_type)
+        .replace("{length}", &chunk.length().to_string())
+        .replace("{crc}", &format!("{:08x}", chunk.crc()))
+}
+
+/// Formats the ancillary, private, reserved and safe-to-copy bits of a chunk type
+fn type_bits(chunk_type: &ChunkType) -> String {
+    let [ancillary, private, reserved, safe_to_copy] = chunk_type.property_bits();
+    format!(
+        "C={} p={} r={} c={}",
+        ancillary, private, reserved, safe_to_copy
+    )
+}
+
+/// Escapes control characters so a message always renders on one line
+fn escape_control(msg: &str) -> String {
+    msg.chars()
+        .map(|c| match c.is_control() {
+            true => c.escape_default().to_string(),
+            false => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("pngme-{}-{}", std::process::id(), name))
+    }
+
+    fn temp_png(name: &str, chunks: &[(&str, &str)]) -> PathBuf {
+        let path = temp_path(name);
+        let chunks = chunks
+            .iter()
+            .map(|(chunk_type, data)| {
+                Chunk::new(
+                    ChunkType::from_str(chunk_type).unwrap(),
+                    data.as_bytes().to_vec(),
+                )
+            })
+            .collect();
+        fs::write(&path, Png::from_chunks(chunks).as_bytes()).unwrap();
+        path
+    }
+
+    fn read_png(path: &Path) -> Png {
+        Png::try_from(fs::read(path).unwrap().as_slice()).unwrap()
+    }
+
+    /// Global flags allowing the in-place writes most tests perform
+    fn force() -> GlobalArgs {
+        GlobalArgs {
+            force: true,
+            ..GlobalArgs::default()
+        }
+    }
+
+    fn encode_args(file_path: &Path, chunk_type: &str, message: &str) -> EncodeArgs {
+        EncodeArgs {
+            file_path: file_path.to_path_buf(),
+            chunk_type: ChunkType::from_str(chunk_type).unwrap(),
+            message: Some(message.to_string()),
+            output_file: None,
+            output: None,
+            messages: Vec::new(),
+            #[cfg(feature = "compress")]
+            compress: false,
+            only_if_present: None,
+            max_message_bytes: None,
+            before: None,
+            after: None,
+            replace_if_changed: false,
+            provenance: false,
+            stamp_time: false,
+            #[cfg(feature = "base64")]
+            base64: false,
+            from_hex: false,
+            keyword: None,
+            language: None,
+            every: None,
+            crc: None,
+            backup: BackupArgs::default(),
+        }
+    }
+
+    #[test]
+    fn test_encode_forced_crc() {
+        let path = temp_png("forced-crc.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "ruSt", "broken");
+        args.crc = Some(0xdeadbeef);
+        encode(args, &force()).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let expected: Vec<u8> = 6u32
+            .to_be_bytes()
+            .iter()
+            .chain(b"ruStbroken")
+            .chain(&0xdeadbeefu32.to_be_bytes())
+            .copied()
+            .collect();
+        assert!(bytes
+            .windows(expected.len())
+            .any(|window| window == expected));
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_only_if_present() {
+        let path = temp_png(
+            "placeholder.png",
+            &[("IHDR", ""), ("plHd", ""), ("IEND", "")],
+        );
+        let mut args = encode_args(&path, "ruSt", "filled in");
+        args.only_if_present = Some(ChunkType::from_str("plHd").unwrap());
+        encode(args, &force()).unwrap();
+        let png = read_png(&path);
+        let chunk = png.chunk_by_type("ruSt").unwrap();
+        assert_eq!(chunk.data_as_string().unwrap(), "filled in");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_only_if_present_missing() {
+        let path = temp_png("no-placeholder.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "ruSt", "filled in");
+        args.only_if_present = Some(ChunkType::from_str("plHd").unwrap());
+        assert!(encode(args, &force()).is_err());
+        assert!(read_png(&path).chunk_by_type("ruSt").is_none());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_repeated_messages() {
+        let path = temp_png(
+            "repeated-messages.png",
+            &[("IHDR", ""), ("IDAT", "pixels"), ("IEND", "")],
+        );
+        let mut args = encode_args(&path, "ruSt", "");
+        args.message = None;
+        args.messages = vec!["one".to_string(), "two".to_string(), "three".to_string()];
+        encode(args, &force()).unwrap();
+
+        let png = read_png(&path);
+        let messages: Vec<String> = png
+            .chunks_by_type("ruSt")
+            .iter()
+            .map(|chunk| chunk.data_as_string().unwrap())
+            .collect();
+        assert_eq!(messages, ["one", "two", "three"]);
+        assert!(png.is_well_ordered());
+
+        let mut args = encode_args(&path, "ruSt", "first");
+        args.messages = vec!["second".to_string()];
+        args.after = Some(ChunkType::from_str("IHDR").unwrap());
+        encode(args, &force()).unwrap();
+        let messages: Vec<String> = read_png(&path)
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.data_as_string().unwrap())
+            .collect();
+        assert_eq!(messages[1..3], ["first", "second"]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_max_message_bytes() {
+        let path = temp_png("max-message-bytes.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "ruSt", "this message is too long");
+        args.max_message_bytes = Some(8);
+        assert!(encode(args, &force()).is_err());
+        assert!(read_png(&path).chunk_by_type("ruSt").is_none());
+
+        let mut args = encode_args(&path, "ruSt", "short");
+        args.max_message_bytes = Some(8);
+        encode(args, &force()).unwrap();
+        assert!(read_png(&path).chunk_by_type("ruSt").is_some());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_check_message_size() {
+        assert!(check_message_size(8, 8).is_ok());
+        assert!(check_message_size(9, 8).is_err());
+        assert!(
+            check_message_size(DEFAULT_MAX_MESSAGE_BYTES + 1, DEFAULT_MAX_MESSAGE_BYTES).is_err()
+        );
+        // The u32 length limit applies even when the configured maximum is larger
+        assert!(check_message_size(u32::MAX as usize, usize::MAX).is_ok());
+        let error = check_message_size(u32::MAX as usize + 1, usize::MAX).unwrap_err();
+        assert!(error.to_string().contains("at most"));
+    }
+
+    #[test]
+    fn test_remove_all() {
+        let path = temp_png(
+            "remove-all.png",
+            &[
+                ("IHDR", ""),
+                ("ruSt", "one"),
+                ("ruSt", "two"),
+                ("teSt", "keep"),
+                ("ruSt", "three"),
+                ("IEND", ""),
+            ],
+        );
+        remove(
+            RemoveArgs {
+                file_path: path.clone(),
+                chunk_type: Some(ChunkType::from_str("ruSt").unwrap()),
+                all: true,
+                index: None,
+                output_file: None,
+                pattern: None,
+                backup: BackupArgs::default(),
+            },
+            &force(),
+        )
+        .unwrap();
+        let png = read_png(&path);
+        assert!(png.chunk_by_type("ruSt").is_none());
+        assert_eq!(png.chunks().len(), 3);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_escape_control() {
+        assert_eq!(escape_control("one\ntwo\tthree"), "one\\ntwo\\tthree");
+        assert_eq!(escape_control("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_extract_zero_pads_names() {
+        let chunks: Vec<(&str, &str)> = (0..12).map(|_| ("ruSt", "data")).collect();
+        let path = temp_png("extract.png", &chunks);
+        let output_dir = temp_path("extract");
+        extract(
+            ExtractArgs {
+                file_path: path.clone(),
+                output_dir: output_dir.clone(),
+                name_template: "{index}_{type}.bin".to_string(),
+            },
+            &GlobalArgs::default(),
+        )
+        .unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        let expected: Vec<String> = (1..=12).map(|i| format!("{:03}_ruSt.bin", i)).collect();
+        assert_eq!(names, expected);
+        fs::remove_dir_all(output_dir).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_type_bits() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(type_bits(&chunk_type), "C=0 p=1 r=0 c=1");
+    }
+
+    #[test]
+    fn test_replace_keeps_position() {
+        let path = temp_png(
+            "replace.png",
+            &[
+                ("IHDR", ""),
+                ("ruSt", "old"),
+                ("teSt", "other"),
+                ("IEND", ""),
+            ],
+        );
+        replace(
+            ReplaceArgs {
+                file_path: path.clone(),
+                chunk_type: ChunkType::from_str("ruSt").unwrap(),
+                message: "new".to_string(),
+                backup: BackupArgs::default(),
+            },
+            &force(),
+        )
+        .unwrap();
+        let png = read_png(&path);
+        assert_eq!(png.chunks().len(), 4);
+        assert_eq!(&png.chunks()[1].chunk_type().to_string(), "ruSt");
+        assert_eq!(png.chunks()[1].data_as_string().unwrap(), "new");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rename_keeps_position_and_data() {
+        let path = temp_png(
+            "rename.png",
+            &[
+                ("IHDR", ""),
+                ("ruSt", "message"),
+                ("ruSt", "second"),
+                ("IEND", ""),
+            ],
+        );
+        let rename_args = || RenameArgs {
+            file_path: path.clone(),
+            from: ChunkType::from_str("ruSt").unwrap(),
+            to: ChunkType::from_str("teSt").unwrap(),
+        };
+        rename(rename_args(), &force()).unwrap();
+        let png = read_png(&path);
+        let renamed = &png.chunks()[1];
+        assert_eq!(&renamed.chunk_type().to_string(), "teSt");
+        assert_eq!(renamed.data_as_string().unwrap(), "message");
+        assert_eq!(
+            renamed.crc(),
+            Chunk::new(ChunkType::from_str("teSt").unwrap(), b"message".to_vec()).crc()
+        );
+        assert_eq!(&png.chunks()[2].chunk_type().to_string(), "ruSt");
+
+        rename(rename_args(), &force()).unwrap();
+        assert!(rename(rename_args(), &force()).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replace_missing_chunk() {
+        let path = temp_png("replace-missing.png", &[("IHDR", ""), ("IEND", "")]);
+        let args = ReplaceArgs {
+            file_path: path.clone(),
+            chunk_type: ChunkType::from_str("ruSt").unwrap(),
+            message: "new".to_string(),
+            backup: BackupArgs::default(),
+        };
+        assert!(replace(args, &force()).is_err());
+        assert_eq!(read_png(&path).chunks().len(), 2);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_before_and_after() {
+        let path = temp_png(
+            "placement.png",
+            &[("IHDR", ""), ("IDAT", "pixels"), ("IEND", "")],
+        );
+        let mut args = encode_args(&path, "beFr", "before");
+        args.before = Some(ChunkType::from_str("IDAT").unwrap());
+        encode(args, &force()).unwrap();
+        let mut args = encode_args(&path, "afTr", "after");
+        args.after = Some(ChunkType::from_str("IDAT").unwrap());
+        encode(args, &force()).unwrap();
+
+        let types: Vec<String> = read_png(&path)
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["IHDR", "beFr", "IDAT", "afTr", "IEND"]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_placement_errors() {
+        let path = temp_png("placement-errors.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "ruSt", "message");
+        args.before = Some(ChunkType::from_str("IDAT").unwrap());
+        assert!(encode(args, &force()).is_err());
+
+        // Giving both --before and --after is rejected by clap, see tests/cli.rs
+
+        let mut args = encode_args(&path, "ruSt", "message");
+        args.after = Some(ChunkType::from_str("IEND").unwrap());
+        assert!(encode(args, &force()).is_err());
+        assert_eq!(read_png(&path).chunks().len(), 2);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_replace_if_changed() {
+        use std::time::{Duration, SystemTime};
+
+        let path = temp_png("replace-if-changed.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "ruSt", "same message");
+        args.replace_if_changed = true;
+        encode(args, &force()).unwrap();
+
+        let old_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old_time)
+            .unwrap();
+
+        let mut args = encode_args(&path, "ruSt", "same message");
+        args.replace_if_changed = true;
+        encode(args, &force()).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), old_time);
+        assert_eq!(read_png(&path).chunks_by_type("ruSt").len(), 1);
+
+        let mut args = encode_args(&path, "ruSt", "new message");
+        args.replace_if_changed = true;
+        encode(args, &force()).unwrap();
+        assert_ne!(fs::metadata(&path).unwrap().modified().unwrap(), old_time);
+        let png = read_png(&path);
+        assert_eq!(png.chunks_by_type("ruSt").len(), 1);
+        assert_eq!(
+            png.chunk_by_type("ruSt").unwrap().data_as_string().unwrap(),
+            "new message"
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_warning_line() {
+        let line = warning_line(&[("kind", "duplicate-type"), ("note", "two words")]);
+        assert_eq!(line, "warning: kind=duplicate-type note=\"two words\"");
+    }
+
+    #[test]
+    fn test_check_version() {
+        let path = temp_png(
+            "check-version.png",
+            &[
+                ("IHDR", ""),
+                (PROVENANCE_CHUNK, "pngme 0.0.9"),
+                ("IEND", ""),
+            ],
+        );
+        let check = |min_version: &str| {
+            check_version(
+                CheckVersionArgs {
+                    file_path: path.clone(),
+                    min_version: min_version.to_string(),
+                },
+                &GlobalArgs::default(),
+            )
+        };
+        assert!(check("0.1.0").is_err());
+        assert!(check("0.0.9").is_ok());
+        assert!(check("0.0.1").is_ok());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_provenance() {
+        let path = temp_png("provenance.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "ruSt", "message");
+        args.provenance = true;
+        encode(args, &force()).unwrap();
+        let png = read_png(&path);
+        let stored = png.chunk_by_type(PROVENANCE_CHUNK).unwrap();
+        assert_eq!(
+            stored.data_as_string().unwrap(),
+            format!("pngme {}", env!("CARGO_PKG_VERSION"))
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.22.3").unwrap(), (1, 22, 3));
+        assert!(parse_version("1.2").is_err());
+        assert!(parse_version("1.x.3").is_err());
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let dump = hex_dump(b"Hello, hex dump!\x00\x01");
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines[0],
+            "00000000  48 65 6c 6c 6f 2c 20 68  65 78 20 64 75 6d 70 21  |Hello, hex dump!|"
+        );
+        assert_eq!(
+            lines[1],
+            "00000010  00 01                                             |..|"
+        );
+        assert!(hex_dump(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_print_hex_includes_binary_chunks() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0, 0, 0, 50]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hi".to_vec()),
+        ]);
+        let args = PrintArgs {
+            hex: true,
+            ..PrintArgs::default()
+        };
+        let mut out = Vec::new();
+        write_chunks(&png, &args, &GlobalArgs::default(), false, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("1: Chunk Type - IHDR, Length: 4"));
+        assert!(out.contains("00000000  00 00 00 32"));
+        assert!(out.contains("2: Chunk Type - ruSt, Length: 2"));
+        assert!(out.ends_with("2 results in total\n"));
+    }
+
+    #[test]
+    fn test_extract_name_template() {
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hello".to_vec());
+        let name = extract_file_name("{type}-{index}-{length}-{crc}.dat", 7, 20, &chunk);
+        assert_eq!(name, format!("ruSt-007-5-{:08x}.dat", chunk.crc()));
+
+        let unsafe_type =
+            Chunk::try_from_trusted(&[0, 0, 0, 0, b'a', b'/', b'.', b'b', 0, 0, 0, 0]);
+        assert!(unsafe_type.is_err());
+    }
+
+    #[test]
+    fn test_print_type_filter() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"one".to_vec()),
+            Chunk::new(ChunkType::from_str("teSt").unwrap(), b"two".to_vec()),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"three".to_vec()),
+        ]);
+        let args = PrintArgs {
+            types: vec![ChunkType::from_str("ruSt").unwrap()],
+            ..PrintArgs::default()
+        };
+        let mut out = Vec::new();
+        write_chunks(&png, &args, &GlobalArgs::default(), false, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out,
+            "1: Chunk Type - ruSt, Msg: one\n2: Chunk Type - ruSt, Msg: three\n2 results in total\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_dry_run_verbose_plan() {
+        let path = temp_png(
+            "dry-run-plan.png",
+            &[("IHDR", ""), ("IDAT", "pixels"), ("IEND", "")],
+        );
+        let original = fs::read(&path).unwrap();
+        let output_file = temp_path("dry-run-plan-out.png");
+        let mut args = encode_args(&path, "ruSt", "message");
+        args.before = Some(ChunkType::from_str("IEND").unwrap());
+        args.output_file = Some(output_file.clone());
+        let global = GlobalArgs {
+            dry_run: true,
+            verbose: true,
+            ..GlobalArgs::default()
+        };
+
+        let mut out = Vec::new();
+        encode_to(args, &global, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("2. insert chunk ruSt at index 2\n"));
+        assert!(out.contains("3. recompute totals: 4 chunks"));
+        assert!(out.contains(&format!("4. would write to {}\n", output_file.display())));
+        assert!(!output_file.exists());
+        assert_eq!(fs::read(&path).unwrap(), original);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_remove_dry_run_verbose_plan() {
+        let path = temp_png(
+            "remove-dry-run-plan.png",
+            &[("IHDR", ""), ("ruSt", "message"), ("IEND", "")],
+        );
+        let original = fs::read(&path).unwrap();
+        let global = GlobalArgs {
+            dry_run: true,
+            verbose: true,
+            ..force()
+        };
+        let args = RemoveArgs {
+            file_path: path.clone(),
+            chunk_type: Some(ChunkType::from_str("ruSt").unwrap()),
+            all: false,
+            index: None,
+            output_file: None,
+            pattern: None,
+            backup: BackupArgs::default(),
+        };
+
+        let mut out = Vec::new();
+        remove_to(args, &global, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("2. remove chunk ruSt at index 1\n"));
+        assert!(out.contains(&format!("would write to {}\n", path.display())));
+        assert_eq!(fs::read(&path).unwrap(), original);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_print_lossy_message() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"caf\xe9".to_vec()),
+            Chunk::new(ChunkType::from_str("biNa").unwrap(), vec![0xff, 0x00]),
+        ]);
+        let mut out = Vec::new();
+        write_chunks(
+            &png,
+            &PrintArgs::default(),
+            &GlobalArgs::default(),
+            false,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: Chunk Type - ruSt, Msg: caf\u{fffd}\n\
+             1 results in total\n"
+        );
+    }
+
+    #[test]
+    fn test_print_show_trailing() {
+        let mut bytes = Png::new_empty().as_bytes();
+        bytes.extend_from_slice(b"appended");
+        let png = Png::try_from(bytes.as_slice()).unwrap();
+        let args = PrintArgs {
+            show_trailing: true,
+            ..PrintArgs::default()
+        };
+        let mut out = Vec::new();
+        write_chunks(&png, &args, &GlobalArgs::default(), false, &mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .ends_with("Trailing data after IEND: 8 bytes\nappended\n1 results in total\n"));
+
+        let mut out = Vec::new();
+        write_chunks(
+            &png,
+            &PrintArgs::default(),
+            &GlobalArgs::default(),
+            false,
+            &mut out,
+        )
+        .unwrap();
+        assert!(!String::from_utf8(out).unwrap().contains("Trailing"));
+    }
+
+    #[test]
+    fn test_print_show_all() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"   ".to_vec()),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), Vec::new()),
+            Chunk::new(ChunkType::from_str("biNa").unwrap(), vec![0xff, 0xfe, 0x00]),
+        ]);
+        let mut out = Vec::new();
+        write_chunks(
+            &png,
+            &PrintArgs::default(),
+            &GlobalArgs::default(),
+            false,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0 results in total\n");
+
+        let args = PrintArgs {
+            show_all: true,
+            ..PrintArgs::default()
+        };
+        let mut out = Vec::new();
+        write_chunks(&png, &args, &GlobalArgs::default(), false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: Chunk Type - ruSt, Msg:    \n\
+             2: Chunk Type - ruSt, Msg: \n\
+             3: Chunk Type - biNa, Msg: <binary 3 bytes>\n\
+             3 results in total\n"
+        );
+    }
+
+    #[test]
+    fn test_print_color() {
+        let mut corrupted =
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"bad".to_vec()).as_bytes();
+        *corrupted.last_mut().unwrap() ^= 0xff;
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), b"header".to_vec()),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"good".to_vec()),
+            Chunk::try_from_trusted(&corrupted).unwrap(),
+        ]);
+        let mut out = Vec::new();
+        write_chunks(
+            &png,
+            &PrintArgs::default(),
+            &GlobalArgs::default(),
+            true,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: Chunk Type - \x1b[1;34mIHDR\x1b[0m, Msg: header\n\
+             2: Chunk Type - \x1b[32mruSt\x1b[0m, Msg: good\n\
+             3: Chunk Type - \x1b[31mruSt\x1b[0m, Msg: bad\n\
+             3 results in total\n"
+        );
+    }
+
+    #[test]
+    fn test_print_offsets() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), b"header".to_vec()),
+            Chunk::new(ChunkType::from_str("skIp").unwrap(), b"hidden".to_vec()),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"message".to_vec()),
+        ]);
+        let args = PrintArgs {
+            offsets: true,
+            types: vec![
+                ChunkType::from_str("IHDR").unwrap(),
+                ChunkType::from_str("ruSt").unwrap(),
+            ],
+            ..PrintArgs::default()
+        };
+        let mut out = Vec::new();
+        write_chunks(&png, &args, &GlobalArgs::default(), false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: Chunk Type - IHDR, Offset: 8, Length: 6, Msg: header\n\
+             2: Chunk Type - ruSt, Offset: 44, Length: 7, Msg: message\n\
+             2 results in total\n"
+        );
+
+        let args = PrintArgs {
+            offsets: true,
+            hex: true,
+            ..args
+        };
+        let mut out = Vec::new();
+        write_chunks(&png, &args, &GlobalArgs::default(), false, &mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("1: Chunk Type - IHDR, Offset: 8, Length: 6\n"));
+    }
+
+    #[test]
+    fn test_print_limit_applies_after_type_filter() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"one".to_vec()),
+            Chunk::new(ChunkType::from_str("skIp").unwrap(), b"other".to_vec()),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"two".to_vec()),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"three".to_vec()),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"four".to_vec()),
+        ]);
+        let args = PrintArgs {
+            limit: Some(2),
+            types: vec![ChunkType::from_str("ruSt").unwrap()],
+            ..PrintArgs::default()
+        };
+        let mut out = Vec::new();
+        write_chunks(&png, &args, &GlobalArgs::default(), false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: Chunk Type - ruSt, Msg: one\n\
+             2: Chunk Type - ruSt, Msg: two\n\
+             2 more chunks omitted by --limit\n\
+             2 results in total\n"
+        );
+    }
+
+    #[test]
+    fn test_print_zero_length_chunk() {
+        let png = Png::from_chunks(vec![Chunk::new(
+            ChunkType::from_str("IEND").unwrap(),
+            Vec::new(),
+        )]);
+        let mut out = Vec::new();
+        write_chunks(
+            &png,
+            &PrintArgs::default(),
+            &GlobalArgs::default(),
+            false,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0 results in total\n");
+
+        let args = PrintArgs {
+            hex: true,
+            types: vec![ChunkType::from_str("IEND").unwrap()],
+            ..PrintArgs::default()
+        };
+        let mut out = Vec::new();
+        write_chunks(&png, &args, &GlobalArgs::default(), false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: Chunk Type - IEND, Length: 0\n1 results in total\n"
+        );
+    }
+
+    #[test]
+    fn test_use_color() {
+        assert!(use_color(ColorChoice::Always, false));
+        assert!(!use_color(ColorChoice::Never, true));
+        assert!(!use_color(ColorChoice::Auto, false));
+    }
+
+    #[test]
+    fn test_dry_run_summary() {
+        let path = temp_png(
+            "dry-run-summary.png",
+            &[("IHDR", ""), ("ruSt", "old"), ("IEND", "")],
+        );
+        let original = fs::read(&path).unwrap();
+        let global = GlobalArgs {
+            dry_run: true,
+            ..force()
+        };
+        let args = ReplaceArgs {
+            file_path: path.clone(),
+            chunk_type: ChunkType::from_str("ruSt").unwrap(),
+            message: "newer".to_string(),
+            backup: BackupArgs::default(),
+        };
+
+        let mut out = Vec::new();
+        replace_to(args, &global, &mut out).unwrap();
+        let expected_size = original.len() + 2;
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "Dry run: would write 3 chunks ({} bytes) to {}\n",
+                expected_size,
+                path.display()
+            )
+        );
+        assert_eq!(fs::read(&path).unwrap(), original);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_copy_chunk_between_pngs() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let source = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), Vec::new()),
+            Chunk::new(chunk_type, b"metadata".to_vec()),
+        ]);
+        let mut dest = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), Vec::new()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()),
+        ]);
+        // The copy goes before IEND, and the returned index says so
+        assert_eq!(copy_chunk(&source, &mut dest, &chunk_type).unwrap(), 1);
+
+        let copied = dest.chunk_by_chunk_type(&chunk_type).unwrap();
+        let original = source.chunk_by_chunk_type(&chunk_type).unwrap();
+        assert_eq!(copied.as_bytes(), original.as_bytes());
+        assert_eq!(dest.chunks().len(), 3);
+        assert_eq!(dest.position_of("ruSt"), Some(1));
+
+        let missing = ChunkType::from_str("noNe").unwrap();
+        assert!(copy_chunk(&source, &mut dest, &missing).is_err());
+    }
+
+    #[test]
+    fn test_encode_stamp_time() {
+        let path = temp_png("stamp-time.png", &[("IHDR", ""), ("IEND", "")]);
+        for _ in 0..2 {
+            let mut args = encode_args(&path, "ruSt", "message");
+            args.stamp_time = true;
+            encode(args, &force()).unwrap();
+        }
+        let png = read_png(&path);
+        let stamps = png.chunks_by_type("tIME");
+        assert_eq!(stamps.len(), 1);
+        let time = ModificationTime::try_from(stamps[0].data()).unwrap();
+        assert!(time.year >= 2024);
+
+        let mut out = Vec::new();
+        write_chunks(
+            &png,
+            &PrintArgs::default(),
+            &GlobalArgs::default(),
+            false,
+            &mut out,
+        )
+        .unwrap();
+        assert!(String::from_utf8(out).unwrap().contains(&format!(
+            "Chunk Type - tIME, Msg: <last modification time: {}>\n",
+            time
+        )));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_decode_raw() {
+        let message = "line one\n\"quoted\"\ttab";
+        let path = temp_png(
+            "decode-raw.png",
+            &[("IHDR", ""), ("ruSt", message), ("IEND", "")],
+        );
+        let decode_args = |raw| DecodeArgs {
+            file_path: path.clone(),
+            chunk_type: ChunkType::from_str("ruSt").ok(),
+            #[cfg(feature = "base64")]
+            base64: false,
+            hex: false,
+            raw,
+            keyword: None,
+            pattern: None,
+            all: false,
+            print0: true,
+        };
+        let mut out = Vec::new();
+        decode_to(decode_args(true), &GlobalArgs::default(), &mut out).unwrap();
+        assert_eq!(out, format!("{}\0", message).as_bytes());
+
+        let mut out = Vec::new();
+        decode_to(decode_args(false), &GlobalArgs::default(), &mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("The chunk is: "));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_decode_hex() {
+        let path = temp_png("hex.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "ruSt", "deadBEEF00");
+        args.from_hex = true;
+        encode(args, &force()).unwrap();
+        assert_eq!(
+            read_png(&path).chunk_by_type("ruSt").unwrap().data(),
+            [0xde, 0xad, 0xbe, 0xef, 0x00]
+        );
+
+        let args = DecodeArgs {
+            file_path: path.clone(),
+            chunk_type: ChunkType::from_str("ruSt").ok(),
+            #[cfg(feature = "base64")]
+            base64: false,
+            hex: true,
+            raw: false,
+            keyword: None,
+            pattern: None,
+            all: false,
+            print0: false,
+        };
+        let mut out = Vec::new();
+        decode_to(args, &GlobalArgs::default(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "deadbeef00\n");
+
+        for invalid in ["abc", "not hex!"] {
+            let mut args = encode_args(&path, "ruSt", invalid);
+            args.from_hex = true;
+            assert!(encode(args, &force()).is_err());
+        }
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_encode_base64() {
+        let path = temp_png("base64.png", &[("IHDR", ""), ("IEND", "")]);
+        let data = vec![0, 159, 146, 150, 255];
+        let mut args = encode_args(&path, "ruSt", &encoding::base64_encode(&data));
+        args.base64 = true;
+        encode(args, &force()).unwrap();
+        let png = read_png(&path);
+        let chunk = png.chunk_by_type("ruSt").unwrap();
+        assert_eq!(chunk.data(), data.as_slice());
+        assert_eq!(
+            encoding::base64_decode(&encoding::base64_encode(chunk.data())).unwrap(),
+            data
+        );
+
+        let mut args = encode_args(&path, "ruSt", "not base64!");
+        args.base64 = true;
+        assert!(encode(args, &force()).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_dedup() {
+        let path = temp_png(
+            "dedup.png",
+            &[
+                ("IHDR", ""),
+                ("ruSt", "note"),
+                ("ruSt", "note"),
+                ("IEND", ""),
+            ],
+        );
+        let args = DedupArgs {
+            file_path: path.clone(),
+            chunk_type: None,
+        };
+        let mut out = Vec::new();
+        dedup_to(args, &force(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Removed 1 duplicate chunks\n"
+        );
+        let png = read_png(&path);
+        assert_eq!(png.chunks_by_type("ruSt").len(), 1);
+        assert_eq!(png.chunk_count(), 3);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_quiet_suppresses_summaries() {
+        let path = temp_png(
+            "quiet.png",
+            &[
+                ("IHDR", ""),
+                ("ruSt", "note"),
+                ("ruSt", "note"),
+                ("IEND", ""),
+            ],
+        );
+        let global = GlobalArgs {
+            quiet: true,
+            ..force()
+        };
+        let args = DedupArgs {
+            file_path: path.clone(),
+            chunk_type: None,
+        };
+        let mut out = Vec::new();
+        dedup_to(args, &global, &mut out).unwrap();
+        assert!(out.is_empty());
+
+        let png = read_png(&path);
+        let mut out = Vec::new();
+        write_chunks(&png, &PrintArgs::default(), &global, false, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: Chunk Type - ruSt, Msg: note\n"
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_validate_verify_lists_every_chunk() {
+        let path = temp_png(
+            "validate.png",
+            &[("IHDR", ""), ("ruSt", "message"), ("IEND", "")],
+        );
+        let args = ValidateArgs {
+            file_path: path.clone(),
+            verify: true,
+            is_png: false,
+        };
+        let mut out = Vec::new();
+        validate_to(args, &GlobalArgs::default(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "0: IHDR pass\n1: ruSt pass\n2: IEND pass\n{} is valid\n",
+                path.display()
+            )
+        );
+
+        // Flip the last byte of the ruSt CRC
+        let mut bytes = fs::read(&path).unwrap();
+        let crc_end = Png::STANDARD_HEADER.len() + 2 * Chunk::META_BYTES + "message".len();
+        bytes[crc_end - 1] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+        let args = ValidateArgs {
+            file_path: path.clone(),
+            verify: true,
+            is_png: false,
+        };
+        let mut out = Vec::new();
+        assert!(validate_to(args, &GlobalArgs::default(), &mut out).is_err());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0: IHDR pass\n1: ruSt fail\n2: IEND pass\n"
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_validate_is_png() {
+        // The signature check passes even though the chunk CRC is broken
+        let path = temp_png("is-png.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+        let validate_args = || ValidateArgs {
+            file_path: path.clone(),
+            verify: false,
+            is_png: true,
+        };
+        let mut out = Vec::new();
+        validate_to(validate_args(), &GlobalArgs::default(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{} is a png\n", path.display())
+        );
+
+        fs::write(&path, "plain text").unwrap();
+        let error =
+            validate_to(validate_args(), &GlobalArgs::default(), &mut Vec::new()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::InvalidSignature)
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_search() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"Buy milk".to_vec()),
+            Chunk::new(ChunkType::from_str("noTe").unwrap(), b"call Bob".to_vec()),
+            Chunk::new(ChunkType::from_str("biNa").unwrap(), vec![0xff, 0xfe]),
+            Chunk::new(ChunkType::from_str("noTe").unwrap(), b"MILK again".to_vec()),
+        ]);
+        let mut args = SearchArgs {
+            file_path: PathBuf::new(),
+            query: "milk".to_string(),
+            ignore_case: false,
+        };
+        let mut out = Vec::new();
+        search_to(&png, &args, &GlobalArgs::default(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ruSt: Buy milk\n1 matches in total\n"
+        );
+
+        args.ignore_case = true;
+        let mut out = Vec::new();
+        search_to(&png, &args, &GlobalArgs::default(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ruSt: Buy milk\nnoTe: MILK again\n2 matches in total\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_by_index() {
+        let path = temp_png(
+            "remove-index.png",
+            &[("IHDR", ""), ("ruSt", "one"), ("ruSt", "two"), ("IEND", "")],
+        );
+        let args = RemoveArgs {
+            file_path: path.clone(),
+            chunk_type: Some(ChunkType::from_str("IEND").unwrap()),
+            all: false,
+            index: Some(2),
+            output_file: None,
+            pattern: None,
+            backup: BackupArgs::default(),
+        };
+        assert!(remove(args, &force()).is_err());
+
+        let args = RemoveArgs {
+            file_path: path.clone(),
+            chunk_type: None,
+            all: false,
+            index: Some(2),
+            output_file: None,
+            pattern: None,
+            backup: BackupArgs::default(),
+        };
+        remove(args, &force()).unwrap();
+        let png = read_png(&path);
+        let messages: Vec<String> = png
+            .chunks_by_type("ruSt")
+            .iter()
+            .map(|chunk| chunk.data_as_string().unwrap())
+            .collect();
+        assert_eq!(messages, ["one"]);
+        assert_eq!(png.chunk_count(), 3);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_classify() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), Vec::new()),
+            Chunk::new(ChunkType::from_str("tEXt").unwrap(), Vec::new()),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), Vec::new()),
+            Chunk::new(ChunkType::from_str("PRIV").unwrap(), Vec::new()),
+        ]);
+        let mut out = Vec::new();
+        write_classification(&png, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0: IHDR - image header\n\
+             1: tEXt - textual data\n\
+             2: ruSt - custom/unknown, ancillary private\n\
+             3: PRIV - custom/unknown, critical public\n"
+        );
+    }
+
+    #[test]
+    fn test_animation_chunks() {
+        let mut actl = 2u32.to_be_bytes().to_vec();
+        actl.extend_from_slice(&0u32.to_be_bytes());
+        let mut fdat = 1u32.to_be_bytes().to_vec();
+        fdat.extend_from_slice(&[0; 10]);
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), b"header".to_vec()),
+            Chunk::new(ChunkType::from_str("acTL").unwrap(), actl),
+            Chunk::new(ChunkType::from_str("fcTL").unwrap(), vec![0; 26]),
+            Chunk::new(ChunkType::from_str("fdAT").unwrap(), fdat),
+        ]);
+
+        let mut out = Vec::new();
+        write_chunks(
+            &png,
+            &PrintArgs::default(),
+            &GlobalArgs::default(),
+            false,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1: Chunk Type - IHDR, Msg: header\n\
+             2: Chunk Type - acTL, Msg: <animation control: 2 frames, loops forever>\n\
+             3: Chunk Type - fcTL, Msg: <frame control: sequence 0>\n\
+             4: Chunk Type - fdAT, Msg: <frame data: sequence 1, 10 bytes>\n\
+             4 results in total\n"
+        );
+
+        let mut out = Vec::new();
+        write_classification(&png, &mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("1: acTL - animation control, 2 frames, loops forever\n"));
+    }
+
+    #[test]
+    fn test_stats() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![0; 100]),
+            Chunk::new(ChunkType::from_str("tEXt").unwrap(), vec![0; 20]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), vec![0; 30]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()),
+        ]);
+        let mut out = Vec::new();
+        write_stats(&png, &mut out).unwrap();
+        // 8 signature bytes, 163 data bytes and 12 bytes of framing per chunk
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "File size: 231 bytes\n\
+             Image data: 100 bytes\n\
+             Other critical data: 13 bytes\n\
+             Standard ancillary data: 20 bytes\n\
+             Custom ancillary data: 30 bytes\n\
+             Ancillary share: 21.6% of the file, 13.0% custom\n\
+             Ancillary to image data ratio: 0.50\n"
+        );
+    }
+
+    #[test]
+    fn test_tree() {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Title\0cat".to_vec()),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![0; 100]),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![0; 50]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hidden".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()),
+        ]);
+        let mut out = Vec::new();
+        write_tree(&png, "cat.png", &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "cat.png (6 chunks)\n\
+             ├── Header (1)\n\
+             │   └── 0: IHDR 13 bytes\n\
+             ├── Image data (2)\n\
+             │   ├── 2: IDAT 100 bytes\n\
+             │   └── 3: IDAT 50 bytes\n\
+             ├── Text (1)\n\
+             │   └── 1: tEXt 9 bytes\n\
+             ├── Custom (1)\n\
+             │   └── 4: ruSt 6 bytes\n\
+             └── End (1)\n\
+             \x20   └── 5: IEND 0 bytes\n"
+        );
+    }
+
+    #[test]
+    fn test_chunk_type_info() {
+        let info = |chunk_type: &str| {
+            let mut out = Vec::new();
+            write_chunk_type_info(&ChunkType::from_str(chunk_type).unwrap(), &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            info("IHDR"),
+            "IHDR: image header\n\
+             \x20 I: critical, decoders must understand it to display the image\n\
+             \x20 H: public, part of the PNG specification or a registered extension\n\
+             \x20 D: reserved bit valid\n\
+             \x20 R: unsafe to copy when an editor changes the image data\n"
+        );
+        assert_eq!(
+            info("ruSt"),
+            "ruSt: custom/unknown\n\
+             \x20 r: ancillary, decoders may ignore it\n\
+             \x20 u: private, defined by an application\n\
+             \x20 S: reserved bit valid\n\
+             \x20 t: safe to copy when an editor changes the image data\n"
+        );
+        assert!(info("tEXt").starts_with("tEXt: textual data\n"));
+        assert!(info("Rust").contains("  s: reserved bit set, the type is invalid in a PNG file\n"));
+    }
+
+    #[test]
+    fn test_batch() {
+        let dir = temp_path("batch");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        let chunks = [("IHDR", ""), ("ruSt", "secret"), ("IEND", "")];
+        for name in ["a.png", "b.png", "nested/c.png"] {
+            let png = temp_png(&format!("batch-{}", name.replace('/', "-")), &chunks);
+            fs::rename(png, dir.join(name)).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "not an image").unwrap();
+        fs::write(dir.join("fake.png"), "not an image either").unwrap();
+
+        let batch_args = |operation, recursive| BatchArgs {
+            dir: dir.clone(),
+            operation,
+            recursive,
+            chunk_type: ChunkType::from_str("ruSt").ok(),
+            jobs: 1,
+        };
+        let mut out = Vec::new();
+        batch_to(
+            batch_args(BatchOperation::Validate, false),
+            &GlobalArgs::default(),
+            &mut out,
+        )
+        .unwrap();
+        let a = dir.join("a.png").display().to_string();
+        let b = dir.join("b.png").display().to_string();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{a}: {a} is valid\n{b}: {b} is valid\n2 of 2 files succeeded\n",
+                a = a,
+                b = b
+            )
+        );
+
+        let mut out = Vec::new();
+        batch_to(batch_args(BatchOperation::Remove, true), &force(), &mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .ends_with("3 of 3 files succeeded\n"));
+        let nested = read_png(&dir.join("nested/c.png"));
+        assert!(nested.chunk_by_type("ruSt").is_none());
+
+        // Every chunk is gone now, so each file reports its own failure
+        let mut out = Vec::new();
+        assert!(batch_to(
+            batch_args(BatchOperation::Remove, false),
+            &force(),
+            &mut out
+        )
+        .is_err());
+        assert!(String::from_utf8(out).unwrap().contains(&format!(
+            "{}: error: Not able to find chunks of type ruSt\n",
+            a
+        )));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_batch_jobs() {
+        let dir = temp_path("batch-jobs");
+        fs::create_dir_all(&dir).unwrap();
+        let names: Vec<String> = (0..12).map(|i| format!("{:02}.png", i)).collect();
+        for name in &names {
+            let png = temp_png(
+                &format!("batch-jobs-{}", name),
+                &[("IHDR", ""), ("ruSt", "secret"), ("IEND", "")],
+            );
+            fs::rename(png, dir.join(name)).unwrap();
+        }
+        fs::write(dir.join("05.png"), "not an image").unwrap();
+        fs::write(dir.join("07.png"), Png::STANDARD_HEADER).unwrap();
+
+        let mut out = Vec::new();
+        let result = batch_to(
+            BatchArgs {
+                dir: dir.clone(),
+                operation: BatchOperation::Validate,
+                recursive: false,
+                chunk_type: None,
+                jobs: 4,
+            },
+            &GlobalArgs::default(),
+            &mut out,
+        );
+        assert!(result.is_err());
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        // 05.png is skipped, 07.png fails, the rest are reported in file order
+        assert_eq!(lines.len(), 12);
+        for (line, name) in lines
+            .iter()
+            .zip(names.iter().filter(|name| *name != "05.png"))
+        {
+            assert!(line.starts_with(&dir.join(name).display().to_string()));
+        }
+        assert!(lines[6].contains(": error: "));
+        assert_eq!(lines[11], "10 of 11 files succeeded");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_decode_all_print0() {
+        let path = temp_png(
+            "decode-print0.png",
+            &[
+                ("IHDR", ""),
+                ("ruSt", "first\nline"),
+                ("tEXt", "other"),
+                ("ruSt", "second"),
+                ("IEND", ""),
+            ],
+        );
+        let decode_args = |all, print0| DecodeArgs {
+            file_path: path.clone(),
+            chunk_type: ChunkType::from_str("ruSt").ok(),
+            #[cfg(feature = "base64")]
+            base64: false,
+            hex: false,
+            raw: false,
+            keyword: None,
+            pattern: None,
+            all,
+            print0,
+        };
+        let global = GlobalArgs {
+            quiet: true,
+            ..GlobalArgs::default()
+        };
+        let mut out = Vec::new();
+        decode_to(decode_args(true, true), &global, &mut out).unwrap();
+        assert_eq!(out, b"first\nline\0second\0");
+
+        let mut out = Vec::new();
+        decode_to(decode_args(false, false), &global, &mut out).unwrap();
+        assert_eq!(out, b"first\nline\n");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_read_input_from_stdin() {
+        let bytes = Png::new_empty().as_bytes();
+        let read = read_input_from(Path::new("-"), bytes.as_slice()).unwrap();
+        assert_eq!(Png::try_from(read.as_slice()).unwrap().chunk_count(), 2);
+
+        // A real path ignores the reader
+        let path = temp_png("stdin-path.png", &[("IHDR", ""), ("IEND", "")]);
+        let read = read_input_from(&path, io::empty()).unwrap();
+        assert_eq!(read, fs::read(&path).unwrap());
+        fs::remove_file(path).unwrap();
+
+        let png = Png::new_empty();
+        let error = write_png(
+            Path::new("-"),
+            &png,
+            png.as_bytes(),
+            Vec::new(),
+            None,
+            &force(),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("stdin"));
+    }
+
+    #[test]
+    fn test_backup() {
+        let path = temp_png("backup.png", &[("IHDR", ""), ("ruSt", "old"), ("IEND", "")]);
+        let original = fs::read(&path).unwrap();
+        let backup = backup_path(&path, ".bak");
+
+        // The backup stands in for --force when overwriting the input
+        let args = ReplaceArgs {
+            file_path: path.clone(),
+            chunk_type: ChunkType::from_str("ruSt").unwrap(),
+            message: "new".to_string(),
+            backup: BackupArgs {
+                backup: Some(".bak".to_string()),
+            },
+        };
+        replace(args, &GlobalArgs::default()).unwrap();
+        assert_eq!(fs::read(&backup).unwrap(), original);
+        let chunk = read_png(&path)
+            .chunk_by_type("ruSt")
+            .unwrap()
+            .data()
+            .to_vec();
+        assert_eq!(chunk, b"new");
+
+        // An existing backup is only replaced with --force
+        let mut args = encode_args(&path, "ruSt", "more");
+        args.backup.backup = Some(".bak".to_string());
+        assert!(encode(args, &GlobalArgs::default()).is_err());
+        assert_eq!(fs::read(&backup).unwrap(), original);
+        let edited = fs::read(&path).unwrap();
+        let mut args = encode_args(&path, "ruSt", "more");
+        args.backup.backup = Some(".bak".to_string());
+        encode(args, &force()).unwrap();
+        assert_eq!(fs::read(&backup).unwrap(), edited);
+
+        // A custom suffix, and no backup when writing to a new output file
+        let output_file = temp_path("backup-output.png");
+        let args = RemoveArgs {
+            file_path: path.clone(),
+            chunk_type: ChunkType::from_str("ruSt").ok(),
+            pattern: None,
+            all: true,
+            index: None,
+            output_file: Some(output_file.clone()),
+            backup: BackupArgs {
+                backup: Some(".orig".to_string()),
+            },
+        };
+        remove(args, &GlobalArgs::default()).unwrap();
+        assert!(!backup_path(&output_file, ".orig").exists());
+        assert!(read_png(&output_file).chunk_by_type("ruSt").is_none());
+        for file in [path, backup, output_file] {
+            fs::remove_file(file).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_export() {
+        let path = temp_png(
+            "export.png",
+            &[
+                ("IHDR", "header"),
+                ("ruSt", "first"),
+                ("IDAT", "pixels"),
+                ("teSt", "second"),
+                ("IEND", ""),
+            ],
+        );
+        let output_dir = temp_path("export");
+        export(
+            ExportArgs {
+                file_path: path.clone(),
+                output_dir: output_dir.clone(),
+            },
+            &GlobalArgs::default(),
+        )
+        .unwrap();
+        assert_eq!(fs::read(output_dir.join("1_ruSt.bin")).unwrap(), b"first");
+        assert_eq!(fs::read(output_dir.join("3_teSt.bin")).unwrap(), b"second");
+        assert!(!output_dir.join("0_IHDR.bin").exists());
+
+        let manifest = fs::read_to_string(output_dir.join("manifest.json")).unwrap();
+        assert!(manifest.contains(
+            "{\"index\": 1, \"type\": \"ruSt\", \"file\": \"1_ruSt.bin\", \"length\": 5}"
+        ));
+        assert!(manifest.contains(
+            "{\"index\": 3, \"type\": \"teSt\", \"file\": \"3_teSt.bin\", \"length\": 6}"
+        ));
+        fs::remove_dir_all(output_dir).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let chunks = [
+            ("IHDR", "header"),
+            ("ruSt", "first"),
+            ("teSt", "second"),
+            ("IEND", ""),
+        ];
+        let source = temp_png("round-trip-source.png", &chunks);
+        let target = temp_png("round-trip-target.png", &[("IHDR", "header"), ("IEND", "")]);
+        let dir = temp_path("round-trip");
+        let global = GlobalArgs {
+            quiet: true,
+            ..force()
+        };
+        export(
+            ExportArgs {
+                file_path: source.clone(),
+                output_dir: dir.clone(),
+            },
+            &global,
+        )
+        .unwrap();
+        let import_args = || ImportArgs {
+            file_path: target.clone(),
+            input_dir: dir.clone(),
+            output_file: None,
+        };
+        import_to(import_args(), &global, &mut Vec::new()).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), fs::read(&source).unwrap());
+
+        // A missing data file fails before anything is written
+        fs::remove_file(dir.join("2_teSt.bin")).unwrap();
+        let before = fs::read(&target).unwrap();
+        let error = import_to(import_args(), &global, &mut Vec::new()).unwrap_err();
+        assert!(error.to_string().contains("does not exist"));
+        assert_eq!(fs::read(&target).unwrap(), before);
+
+        // Manifest files must stay inside the input directory
+        let outside = temp_path("round-trip-outside.bin");
+        fs::write(&outside, "secret").unwrap();
+        for file in ["../x", outside.to_str().unwrap()] {
+            let entry = ManifestEntry {
+                index: 1,
+                chunk_type: ChunkType::from_str("ruSt").unwrap(),
+                file: file.to_string(),
+                length: 6,
+            };
+            fs::write(
+                dir.join(manifest::MANIFEST_FILE),
+                manifest::to_json("a.png", &[entry]),
+            )
+            .unwrap();
+            let error = import_to(import_args(), &global, &mut Vec::new()).unwrap_err();
+            assert!(error.to_string().contains("plain file name"));
+            assert_eq!(fs::read(&target).unwrap(), before);
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_file(outside).unwrap();
+        fs::remove_file(source).unwrap();
+        fs::remove_file(target).unwrap();
+    }
+
+    #[test]
+    fn test_progress_line() {
+        assert_eq!(progress_line(0, 4), "[--------------------] 0/4 files");
+        assert_eq!(progress_line(1, 4), "[#####---------------] 1/4 files");
+        assert_eq!(progress_line(4, 4), "[####################] 4/4 files");
+        assert_eq!(progress_line(0, 0), "[####################] 0/0 files");
+    }
+
+    #[test]
+    fn test_in_place_write_needs_force() {
+        let path = temp_png("needs-force.png", &[("IHDR", ""), ("IEND", "")]);
+        let original = fs::read(&path).unwrap();
+        let error = encode(
+            encode_args(&path, "ruSt", "message"),
+            &GlobalArgs::default(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("--force"));
+        assert_eq!(fs::read(&path).unwrap(), original);
+
+        // A dry run refuses the same write
+        let dry_run = GlobalArgs {
+            dry_run: true,
+            ..GlobalArgs::default()
+        };
+        let error = encode_to(
+            encode_args(&path, "ruSt", "message"),
+            &dry_run,
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("--force"));
+
+        encode(encode_args(&path, "ruSt", "message"), &force()).unwrap();
+        assert!(read_png(&path).chunk_by_type("ruSt").is_some());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_remove_to_output_file() {
+        let path = temp_png(
+            "remove-output.png",
+            &[("IHDR", ""), ("ruSt", "message"), ("IEND", "")],
+        );
+        let output_file = temp_path("remove-output-result.png");
+        let original = fs::read(&path).unwrap();
+        let args = RemoveArgs {
+            file_path: path.clone(),
+            chunk_type: Some(ChunkType::from_str("ruSt").unwrap()),
+            all: false,
+            index: None,
+            output_file: Some(output_file.clone()),
+            pattern: None,
+            backup: BackupArgs::default(),
+        };
+        remove(args, &force()).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), original);
+        assert!(read_png(&output_file).chunk_by_type("ruSt").is_none());
+        fs::remove_file(path).unwrap();
+        fs::remove_file(output_file).unwrap();
+    }
+
+    #[test]
+    fn test_text_keyword_round_trip() {
+        let path = temp_png("text-keyword.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "tEXt", "my note");
+        args.keyword = Some("Comment".to_string());
+        encode(args, &force()).unwrap();
+        let mut args = encode_args(&path, "tEXt", "Jane");
+        args.keyword = Some("Author".to_string());
+        encode(args, &force()).unwrap();
+        assert_eq!(
+            read_png(&path).chunk_by_type("tEXt").unwrap().data(),
+            b"Comment\0my note"
+        );
+
+        let decode_args = |keyword: &str| DecodeArgs {
+            file_path: path.clone(),
+            chunk_type: Some(ChunkType::from_str("tEXt").unwrap()),
+            #[cfg(feature = "base64")]
+            base64: false,
+            hex: false,
+            raw: false,
+            keyword: Some(keyword.to_string()),
+            pattern: None,
+            all: false,
+            print0: false,
+        };
+        let global = GlobalArgs {
+            quiet: true,
+            ..GlobalArgs::default()
+        };
+        let mut out = Vec::new();
+        decode_to(decode_args("Author"), &global, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Jane\n");
+        let mut out = Vec::new();
+        decode_to(decode_args("Comment"), &global, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "my note\n");
+        assert!(decode_to(decode_args("Title"), &global, &mut Vec::new()).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_international_text_round_trip() {
+        let path = temp_png("international-text.png", &[("IHDR", ""), ("IEND", "")]);
+        let mut args = encode_args(&path, "iTXt", "吾輩は猫である");
+        args.keyword = Some("Title".to_string());
+        args.language = Some("ja".to_string());
+        encode(args, &force()).unwrap();
+        let png = read_png(&path);
+        let stored = InternationalText::try_from(png.chunk_by_type("iTXt").unwrap().data());
+        assert_eq!(stored.unwrap().language_tag(), "ja");
+
+        let decode_args = DecodeArgs {
+            file_path: path.clone(),
+            chunk_type: Some(ChunkType::from_str("iTXt").unwrap()),
+            #[cfg(feature = "base64")]
+            base64: false,
+            hex: false,
+            raw: true,
+            keyword: Some("Title".to_string()),
+            pattern: None,
+            all: false,
+            print0: false,
+        };
+        let mut out = Vec::new();
+        decode_to(decode_args, &GlobalArgs::default(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "吾輩は猫である\n");
+
+        let mut args = encode_args(&path, "tEXt", "note");
+        args.keyword = Some("Comment".to_string());
+        args.language = Some("en".to_string());
+        assert!(encode(args, &force()).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_text_payload_rejects_bad_keywords() {
+        assert!(text_payload("", b"text").is_err());
+        assert!(text_payload(&"k".repeat(80), b"text").is_err());
+        assert!(text_payload("a\0b", b"text").is_err());
+        assert_eq!(text_payload("k", b"").unwrap(), b"k\0");
+    }
+
+    #[test]
+    fn test_encode_every() {
+        let path = temp_png(
+            "every.png",
+            &[
+                ("IHDR", ""),
+                ("aaAa", ""),
+                ("bbBb", ""),
+                ("ccCc", ""),
+                ("ddDd", ""),
+                ("IEND", ""),
+            ],
+        );
+        let mut args = encode_args(&path, "wmRk", "mark");
+        args.every = Some(2);
+        encode(args, &force()).unwrap();
+
+        let types: Vec<String> = read_png(&path)
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(
+            types,
+            ["IHDR", "aaAa", "bbBb", "wmRk", "ccCc", "ddDd", "IEND"]
+        );
+
+        let mut args = encode_args(&path, "wmRk", "mark");
+        args.every = Some(8);
+        assert!(encode(args, &force()).is_err());
+        fs::remove_file(path).unwrap();
+
+        // Copies never split the IDAT run or follow IHDR directly
+        let path = temp_png(
+            "every-idat.png",
+            &[
+                ("IHDR", ""),
+                ("tEXt", ""),
+                ("IDAT", ""),
+                ("IDAT", ""),
+                ("IDAT", ""),
+                ("aaAa", ""),
+                ("bbBb", ""),
+                ("IEND", ""),
+            ],
+        );
+        let mut args = encode_args(&path, "wmRk", "mark");
+        args.every = Some(1);
+        encode(args, &force()).unwrap();
+        let png = read_png(&path);
+        let types: Vec<String> = png.chunks().iter().map(Chunk::type_str).collect();
+        assert_eq!(
+            types,
+            ["IHDR", "tEXt", "IDAT", "IDAT", "IDAT", "aaAa", "wmRk", "bbBb", "IEND"]
+        );
+        png.validate_order().unwrap();
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_pattern_matching_commands() {
+        let path = temp_png(
+            "pattern.png",
+            &[
+                ("IHDR", ""),
+                ("ruSt", "one"),
+                ("coSt", "two"),
+                ("ruSx", "three"),
+                ("IEND", ""),
+            ],
+        );
+        assert_eq!(count_matching(&read_png(&path), "??St"), 2);
+        assert_eq!(count_matching(&read_png(&path), "??ST"), 0);
+
+        let args = DecodeArgs {
+            file_path: path.clone(),
+            chunk_type: None,
+            #[cfg(feature = "base64")]
+            base64: false,
+            hex: false,
+            raw: false,
+            keyword: None,
+            pattern: Some("??St".to_string()),
+            all: false,
+            print0: false,
+        };
+        let global = GlobalArgs {
+            quiet: true,
+            ..GlobalArgs::default()
+        };
+        let mut out = Vec::new();
+        decode_to(args, &global, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "one\ntwo\n");
+
+        let args = RemoveArgs {
+            file_path: path.clone(),
+            chunk_type: None,
+            all: false,
+            index: None,
+            output_file: None,
+            pattern: Some("??St".to_string()),
+            backup: BackupArgs::default(),
+        };
+        remove(args, &force()).unwrap();
+        let types: Vec<String> = read_png(&path)
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["IHDR", "ruSx", "IEND"]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_compare() {
+        let path = temp_png(
+            "compare.png",
+            &[("IHDR", ""), ("ruSt", "one"), ("ruSt", "two"), ("IEND", "")],
+        );
+        let original = read_png(&path);
+        let mut edited = read_png(&path);
+        edited.replace_chunk("IHDR", b"header".to_vec()).unwrap();
+        edited.remove_chunk_at(2).unwrap();
+        edited.append_chunk(Chunk::new(ChunkType::from_str("neWw").unwrap(), Vec::new()));
+
+        let mut out = Vec::new();
+        write_comparison(&original, &edited, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "~ IHDR #1: length 0 -> 6, crc {:08x} -> {:08x}\n\
+                 - ruSt #2\n\
+                 + neWw #1\n\
+                 3 differences\n",
+                original.chunks()[0].crc(),
+                edited.chunks()[0].crc()
+            )
+        );
+
+        let mut out = Vec::new();
+        write_comparison(&original, &read_png(&path), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "identical chunk structure\n"
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+use std::io::{Read, Write};
+
+use anyhow::bail;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::Result;
+
+/// Prefix marking chunk data that was compressed by `pngme encode --compress`
+pub const MAGIC: [u8; 4] = [0, b'p', b'z', 1];
+
+/// Returns true if the data starts with the compression magic prefix
+pub fn is_compressed(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Deflates the data into a zlib stream and prepends the magic prefix
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(MAGIC.to_vec(), Compression::default());
+    // Writing into a Vec cannot fail
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Reverses `compress`, failing if the magic prefix is missing or the rest is not a
+/// valid zlib stream
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    if !is_compressed(data) {
+        bail!("Data is not compressed");
+    }
+    let mut output = Vec::new();
+    ZlibDecoder::new(&data[MAGIC.len()..]).read_to_end(&mut output)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_compressible_message() {
+        let message = "all work and no play makes jack a dull boy. ".repeat(200);
+        let compressed = compress(message.as_bytes());
+        assert!(is_compressed(&compressed));
+        assert!(compressed.len() < message.len() / 10);
+        assert_eq!(decompress(&compressed).unwrap(), message.as_bytes());
+    }
+
+    #[test]
+    fn test_stream_is_plain_zlib() {
+        let compressed = compress(b"message");
+        let mut inflated = Vec::new();
+        ZlibDecoder::new(&compressed[MAGIC.len()..])
+            .read_to_end(&mut inflated)
+            .unwrap();
+        assert_eq!(inflated, b"message");
+        assert!(decompress(&compress(&[])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decompress_rejects_plain_data() {
+        assert!(decompress(b"plain message").is_err());
+        assert!(decompress(&[&MAGIC[..], b"plain message"].concat()).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::bail;
+#[cfg(feature = "base64")]
+use anyhow::Context;
+#[cfg(feature = "base64")]
+use base64::alphabet;
+#[cfg(feature = "base64")]
+use base64::engine::general_purpose::{self, GeneralPurpose};
+#[cfg(feature = "base64")]
+use base64::engine::{DecodePaddingMode, Engine};
+
+use crate::Result;
+
+/// The standard alphabet, written padded but read with or without padding
+#[cfg(feature = "base64")]
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    general_purpose::PAD.with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Encodes bytes as standard padded base64
+#[cfg(feature = "base64")]
+pub fn base64_encode(data: &[u8]) -> String {
+    BASE64.encode(data)
+}
+
+/// Decodes standard base64, padding is optional and whitespace is ignored
+#[cfg(feature = "base64")]
+pub fn base64_decode(input: &str) -> Result<Vec<u8>> {
+    let input: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    BASE64.decode(input).context("Invalid base64")
+}
+
+/// Encodes bytes as lowercase hex, two digits per byte
+pub fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decodes hex in either case, failing on odd-length input or non-hex characters
+pub fn hex_decode(input: &str) -> Result<Vec<u8>> {
+    if !input.len().is_multiple_of(2) {
+        bail!("Invalid hex: odd number of digits");
+    }
+    input
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let digits = std::str::from_utf8(pair)?;
+            match u8::from_str_radix(digits, 16) {
+                Ok(byte) if pair.iter().all(u8::is_ascii_hexdigit) => Ok(byte),
+                _ => bail!("Invalid hex digits {:?}", digits),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_base64_round_trip() {
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(base64_decode(&base64_encode(&data)).unwrap(), data);
+        assert_eq!(base64_decode("Zm9vYg").unwrap(), b"foob");
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_base64_decode_invalid() {
+        assert!(base64_decode("Zm9v!").is_err());
+        assert!(base64_decode("Zg==Zg").is_err());
+        assert!(base64_decode("Z").is_err());
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(hex_encode(&[0xde, 0xad, 0xbe, 0xef, 0x01]), "deadbeef01");
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(hex_decode(&hex_encode(&data)).unwrap(), data);
+        assert_eq!(hex_decode("DEADbeef").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert!(hex_decode("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hex_decode_invalid() {
+        assert!(hex_decode("abc").is_err());
+        assert!(hex_decode("zz").is_err());
+        assert!(hex_decode("+1").is_err());
+        assert!(hex_decode("é1").is_err());
+    }
+}
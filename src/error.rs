@@ -0,0 +1,123 @@
+use std::fmt::Display;
+
+/// Reasons parsing or editing a PNG can fail.
+///
+/// The CLI exits with a code per category: 1 for other failures, 2 for invalid
+/// arguments, 3 for IO errors, 4 for invalid PNG input and 5 when a chunk is not found.
+///
+/// Library functions still return the crate-wide `anyhow::Error`, which wraps these,
+/// so match on a failure with `error.downcast_ref::<PngError>()`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PngError {
+    /// The input does not start with the 8 byte PNG signature
+    InvalidSignature,
+    /// The stored CRC of a chunk does not match the one computed from its type and data
+    BadCrc { expected: u32, actual: u32 },
+    /// The input ends in the middle of a chunk
+    TruncatedChunk,
+    /// The chunk type is not 4 valid bytes, holds the rejected input
+    InvalidChunkType(String),
+    /// No chunk of the given type exists
+    ChunkNotFound(String),
+    /// An index is past the end of the chunk list
+    IndexOutOfRange { index: usize, len: usize },
+    /// The chunks do not start with IHDR and end with IEND
+    InvalidOrder,
+    /// A chunk breaks the ordering rules of the spec, holds the first violation found
+    ChunkOrder(String),
+    /// The operation would remove a critical chunk the image needs
+    CriticalChunk(String),
+    /// Chunk data is longer than the u32 length field can store, holds the data length
+    DataTooLong(usize),
+}
+
+impl Display for PngError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PngError::InvalidSignature => write!(f, "Incorrect headers!"),
+            PngError::BadCrc { expected, actual } => write!(
+                f,
+                "Invalid crc: expected {:#010x}, found {:#010x}",
+                expected, actual
+            ),
+            PngError::TruncatedChunk => write!(f, "Truncated chunk"),
+            PngError::InvalidChunkType(input) => write!(f, "Invalid chunk type {:?}", input),
+            PngError::ChunkNotFound(chunk_type) => {
+                write!(f, "Not able to find chunks of type {}", chunk_type)
+            }
+            PngError::IndexOutOfRange { index, len } => {
+                write!(f, "Index {} is out of range for {} chunks", index, len)
+            }
+            PngError::InvalidOrder => write!(f, "Chunks must start with IHDR and end with IEND"),
+            PngError::ChunkOrder(violation) => write!(f, "Invalid chunk order: {}", violation),
+            PngError::CriticalChunk(chunk_type) => {
+                write!(f, "Refusing to remove critical chunk {}", chunk_type)
+            }
+            PngError::DataTooLong(len) => write!(
+                f,
+                "Chunk data is {} bytes, but a chunk holds at most {} bytes",
+                len,
+                u32::MAX
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PngError {}
+
+/// Process exit code for failures that fit no other category
+pub const EXIT_FAILURE: u8 = 1;
+/// Process exit code for invalid command line arguments, matching what clap uses
+pub const EXIT_USAGE: u8 = 2;
+/// Process exit code for errors reading or writing files
+pub const EXIT_IO: u8 = 3;
+/// Process exit code for input that is not a valid PNG
+pub const EXIT_PARSE: u8 = 4;
+/// Process exit code when the requested chunk, keyword or index does not exist
+pub const EXIT_NOT_FOUND: u8 = 5;
+
+impl PngError {
+    /// The process exit code reported for this error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            PngError::InvalidSignature
+            | PngError::BadCrc { .. }
+            | PngError::TruncatedChunk
+            | PngError::InvalidChunkType(_)
+            | PngError::InvalidOrder
+            | PngError::ChunkOrder(_) => EXIT_PARSE,
+            PngError::ChunkNotFound(_) | PngError::IndexOutOfRange { .. } => EXIT_NOT_FOUND,
+            PngError::CriticalChunk(_) | PngError::DataTooLong(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+/// Picks the exit code for an error from the first `PngError` or IO error in its chain
+pub fn exit_code(error: &anyhow::Error) -> u8 {
+    error
+        .chain()
+        .find_map(|cause| match cause.downcast_ref::<PngError>() {
+            Some(error) => Some(error.exit_code()),
+            None => cause.downcast_ref::<std::io::Error>().map(|_| EXIT_IO),
+        })
+        .unwrap_or(EXIT_FAILURE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_code() {
+        let not_found = Err::<(), _>(PngError::ChunkNotFound("ruSt".to_string()))
+            .context("Reference chunk ruSt not found")
+            .unwrap_err();
+        assert_eq!(exit_code(&not_found), EXIT_NOT_FOUND);
+        assert_eq!(exit_code(&PngError::InvalidSignature.into()), EXIT_PARSE);
+
+        let missing = std::fs::read("/nonexistent/pngme.png").unwrap_err();
+        assert_eq!(exit_code(&missing.into()), EXIT_IO);
+        assert_eq!(exit_code(&anyhow::anyhow!("other")), EXIT_FAILURE);
+    }
+}
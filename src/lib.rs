@@ -1,8 +1,17 @@
 pub mod args;
-mod chunk;
-mod chunk_type;
+pub mod chunk;
+pub mod chunk_type;
 pub mod commands;
-mod png;
+#[cfg(feature = "compress")]
+mod compress;
+mod encoding;
+pub mod error;
+mod manifest;
+pub mod png;
+pub mod text;
+pub mod time;
+
+pub use error::PngError;
 
 pub type Error = anyhow::Error;
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,17 +1,52 @@
+use std::process::ExitCode;
+
 use anyhow::{self, Ok};
 use clap::Parser;
 use pngme::{
     args::{Cli, Commands},
-    commands,
+    commands, error,
 };
 
-fn main() -> pngme::Result<()> {
+fn main() -> ExitCode {
+    match run() {
+        std::result::Result::Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            ExitCode::from(error::exit_code(&err))
+        }
+    }
+}
+
+fn run() -> pngme::Result<()> {
     let cli = Cli::parse();
     match cli.commands {
-        Commands::Encode(encode_args) => commands::encode(encode_args)?,
-        Commands::Decode(decode_args) => commands::decode(decode_args)?,
-        Commands::Remove(remove_args) => commands::remove(remove_args)?,
-        Commands::Print(print_args) => commands::print_chunks(print_args)?,
+        Commands::Encode(encode_args) => commands::encode(encode_args, &cli.global)?,
+        Commands::Decode(decode_args) => commands::decode(decode_args, &cli.global)?,
+        Commands::Remove(remove_args) => commands::remove(remove_args, &cli.global)?,
+        Commands::Print(print_args) => commands::print_chunks(print_args, &cli.global)?,
+        Commands::Replace(replace_args) => commands::replace(replace_args, &cli.global)?,
+        Commands::Rename(rename_args) => commands::rename(rename_args, &cli.global)?,
+        Commands::Count(count_args) => commands::count(count_args, &cli.global)?,
+        Commands::Extract(extract_args) => commands::extract(extract_args, &cli.global)?,
+        Commands::AppendFrom(append_from_args) => {
+            commands::append_from(append_from_args, &cli.global)?
+        }
+        Commands::Dedup(dedup_args) => commands::dedup(dedup_args, &cli.global)?,
+        Commands::CheckVersion(check_version_args) => {
+            commands::check_version(check_version_args, &cli.global)?
+        }
+        Commands::Validate(validate_args) => commands::validate(validate_args, &cli.global)?,
+        Commands::Search(search_args) => commands::search(search_args, &cli.global)?,
+        Commands::Classify(classify_args) => commands::classify(classify_args, &cli.global)?,
+        Commands::Compare(compare_args) => commands::compare(compare_args, &cli.global)?,
+        Commands::Stats(stats_args) => commands::stats(stats_args, &cli.global)?,
+        Commands::Tree(tree_args) => commands::tree(tree_args, &cli.global)?,
+        Commands::ChunkTypeInfo(chunk_type_info_args) => {
+            commands::chunk_type_info(chunk_type_info_args)?
+        }
+        Commands::Batch(batch_args) => commands::batch(batch_args, &cli.global)?,
+        Commands::Export(export_args) => commands::export(export_args, &cli.global)?,
+        Commands::Import(import_args) => commands::import(import_args, &cli.global)?,
     }
     Ok(())
 }
@@ -0,0 +1,386 @@
+use anyhow::bail;
+
+use crate::chunk_type::ChunkType;
+use crate::Result;
+
+/// One exported chunk: its position in the source PNG, its type, and the file holding its data
+#[derive(Debug, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub index: usize,
+    pub chunk_type: ChunkType,
+    pub file: String,
+    pub length: u32,
+}
+
+/// Name of the manifest file written next to the exported chunk data
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Deepest nesting of objects and arrays accepted, a manifest itself only needs 3
+const MAX_DEPTH: usize = 32;
+
+/// Serializes the manifest as JSON, one chunk object per line
+pub fn to_json(source: &str, entries: &[ManifestEntry]) -> String {
+    let chunks: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "    {{\"index\": {}, \"type\": {}, \"file\": {}, \"length\": {}}}",
+                entry.index,
+                json_string(&entry.chunk_type.to_string()),
+                json_string(&entry.file),
+                entry.length
+            )
+        })
+        .collect();
+    let chunks = match chunks.is_empty() {
+        true => "[]".to_string(),
+        false => format!("[\n{}\n  ]", chunks.join(",\n")),
+    };
+    format!(
+        "{{\n  \"source\": {},\n  \"chunks\": {}\n}}\n",
+        json_string(source),
+        chunks
+    )
+}
+
+/// Parses a manifest written by `to_json`, returning its chunks in manifest order.
+/// Fails if a chunk is missing a field or records an invalid chunk type
+pub fn from_json(input: &str) -> Result<Vec<ManifestEntry>> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        position: 0,
+        depth: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.position != parser.input.len() {
+        bail!(
+            "Invalid manifest: trailing data at byte {}",
+            parser.position
+        );
+    }
+    let chunks = match value.field("chunks") {
+        Some(Json::Array(chunks)) => chunks,
+        _ => bail!("Invalid manifest: expected a \"chunks\" array"),
+    };
+    chunks
+        .iter()
+        .map(|chunk| {
+            let number = |name: &str| match chunk.field(name) {
+                Some(Json::Number(number)) => Ok(*number),
+                _ => bail!("Invalid manifest: chunk is missing the number {:?}", name),
+            };
+            let string = |name: &str| match chunk.field(name) {
+                Some(Json::String(string)) => Ok(string.clone()),
+                _ => bail!("Invalid manifest: chunk is missing the string {:?}", name),
+            };
+            let length = number("length")?;
+            Ok(ManifestEntry {
+                index: number("index")? as usize,
+                chunk_type: ChunkType::from_str_strict(&string("type")?)?,
+                file: string("file")?,
+                length: match u32::try_from(length) {
+                    Ok(length) => length,
+                    Err(_) => bail!("Invalid manifest: chunk length {} is too large", length),
+                },
+            })
+        })
+        .collect()
+}
+
+/// The subset of JSON values a manifest uses, numbers are non-negative integers
+enum Json {
+    Null,
+    Bool,
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn field(&self, name: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .input
+            .get(self.position)
+            .is_some_and(|byte| byte.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<()> {
+        match self.peek() {
+            Some(byte) if byte == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => bail!(
+                "Invalid manifest: expected {:?} at byte {}",
+                expected as char,
+                self.position
+            ),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json> {
+        match self.input[self.position..].starts_with(word.as_bytes()) {
+            true => {
+                self.position += word.len();
+                Ok(value)
+            }
+            false => bail!(
+                "Invalid manifest: unexpected value at byte {}",
+                self.position
+            ),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json> {
+        match self.peek() {
+            Some(b'{') => self.nested(Parser::object),
+            Some(b'[') => self.nested(Parser::array),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'0'..=b'9') => self.number(),
+            Some(b't') => self.literal("true", Json::Bool),
+            Some(b'f') => self.literal("false", Json::Bool),
+            Some(b'n') => self.literal("null", Json::Null),
+            _ => bail!(
+                "Invalid manifest: unexpected value at byte {}",
+                self.position
+            ),
+        }
+    }
+
+    /// Parses an object or array one level deeper, failing past `MAX_DEPTH` so deeply
+    /// nested input cannot overflow the stack
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Json>) -> Result<Json> {
+        if self.depth == MAX_DEPTH {
+            bail!(
+                "Invalid manifest: nested deeper than {} levels at byte {}",
+                MAX_DEPTH,
+                self.position
+            );
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<Json> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(b':')?;
+            fields.push((key, self.value()?));
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                _ => break,
+            }
+        }
+        self.expect(b'}')?;
+        Ok(Json::Object(fields))
+    }
+
+    fn array(&mut self) -> Result<Json> {
+        self.expect(b'[')?;
+        let mut values = Vec::new();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                _ => break,
+            }
+        }
+        self.expect(b']')?;
+        Ok(Json::Array(values))
+    }
+
+    fn number(&mut self) -> Result<Json> {
+        let start = self.position;
+        while self
+            .input
+            .get(self.position)
+            .is_some_and(|byte| byte.is_ascii_digit())
+        {
+            self.position += 1;
+        }
+        let digits = std::str::from_utf8(&self.input[start..self.position])?;
+        match digits.parse() {
+            Ok(number) => Ok(Json::Number(number)),
+            Err(_) => bail!("Invalid manifest: number {} is too large", digits),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = match self.input.get(self.position) {
+                Some(&byte) => byte,
+                None => bail!("Invalid manifest: unterminated string"),
+            };
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = match self.input.get(self.position) {
+                        Some(&escaped) => escaped,
+                        None => bail!("Invalid manifest: unterminated string"),
+                    };
+                    self.position += 1;
+                    match escaped {
+                        b'"' | b'\\' | b'/' => bytes.push(escaped),
+                        b'n' => bytes.push(b'\n'),
+                        b'r' => bytes.push(b'\r'),
+                        b't' => bytes.push(b'\t'),
+                        b'u' => {
+                            let hex = match self.input.get(self.position..self.position + 4) {
+                                Some(hex) => std::str::from_utf8(hex)?,
+                                None => bail!("Invalid manifest: truncated unicode escape"),
+                            };
+                            self.position += 4;
+                            let c = match char::from_u32(u32::from_str_radix(hex, 16)?) {
+                                Some(c) => c,
+                                None => bail!("Invalid manifest: bad unicode escape {}", hex),
+                            };
+                            bytes.extend_from_slice(c.to_string().as_bytes());
+                        }
+                        _ => bail!("Invalid manifest: bad escape \\{}", escaped as char),
+                    }
+                }
+                byte => bytes.push(byte),
+            }
+        }
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+/// Quotes a string for JSON, escaping quotes, backslashes and control characters
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_to_json() {
+        let entries = [ManifestEntry {
+            index: 1,
+            chunk_type: ChunkType::from_str("ruSt").unwrap(),
+            file: "1_ruSt.bin".to_string(),
+            length: 5,
+        }];
+        assert_eq!(
+            to_json("dir/a \"b\".png", &entries),
+            "{\n  \"source\": \"dir/a \\\"b\\\".png\",\n  \"chunks\": [\n    \
+             {\"index\": 1, \"type\": \"ruSt\", \"file\": \"1_ruSt.bin\", \"length\": 5}\n  ]\n}\n"
+        );
+        assert_eq!(
+            to_json("a.png", &[]),
+            "{\n  \"source\": \"a.png\",\n  \"chunks\": []\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_from_json_round_trip() {
+        let entries = vec![
+            ManifestEntry {
+                index: 1,
+                chunk_type: ChunkType::from_str("ruSt").unwrap(),
+                file: "1_ruSt.bin".to_string(),
+                length: 5,
+            },
+            ManifestEntry {
+                index: 4,
+                chunk_type: ChunkType::from_str("teSt").unwrap(),
+                file: "odd \"name\"\n.bin".to_string(),
+                length: 0,
+            },
+        ];
+        let json = to_json("dir\\a.png", &entries);
+        assert_eq!(from_json(&json).unwrap(), entries);
+        assert!(from_json(&to_json("a.png", &[])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_from_json_rejects_bad_manifests() {
+        assert!(from_json("").is_err());
+        assert!(from_json("{\"chunks\": [}").is_err());
+        assert!(from_json("{\"chunks\": []} extra").is_err());
+        assert!(from_json("{\"other\": []}").is_err());
+        let missing_file = "{\"chunks\": [{\"index\": 1, \"type\": \"ruSt\", \"length\": 2}]}";
+        assert!(from_json(missing_file).is_err());
+        let bad_type =
+            "{\"chunks\": [{\"index\": 1, \"type\": \"Rust\", \"file\": \"a\", \"length\": 2}]}";
+        assert!(from_json(bad_type).is_err());
+    }
+
+    #[test]
+    fn test_from_json_rejects_deep_nesting() {
+        let error = from_json(&"[".repeat(100_000)).unwrap_err();
+        assert!(error.to_string().contains("nested deeper"));
+        let nested = format!(
+            "{{\"chunks\": [], \"extra\": {}{}}}",
+            "[".repeat(20),
+            "]".repeat(20)
+        );
+        assert!(from_json(&nested).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("a\\b\n\u{1}"), "\"a\\\\b\\n\\u0001\"");
+    }
+}
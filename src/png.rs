@@ -1,38 +1,186 @@
 use anyhow::{bail, Ok};
 
 pub use crate::chunk_type::ChunkType;
-pub use crate::{chunk::Chunk, Error, Result};
+pub use crate::{chunk::Chunk, Error, PngError, Result};
 use std::fmt::Display;
+use std::io::{self, Read};
+use std::ops::Range;
+
+use crate::chunk;
 
 pub struct Png {
     standard_header: [u8; 8],
     chunks: Vec<Chunk>,
+    trailing_data: Vec<u8>,
+}
+
+/// The frame and loop counts stored in the `acTL` chunk of an animated PNG
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AnimationControl {
+    pub num_frames: u32,
+    /// How many times the animation plays, 0 means it loops forever
+    pub num_plays: u32,
+}
+
+impl TryFrom<&Chunk> for AnimationControl {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self> {
+        let data: [u8; 8] = match chunk.data().try_into() {
+            std::result::Result::Ok(data) => data,
+            Err(_) => bail!("acTL data must be 8 bytes, found {}", chunk.length()),
+        };
+        Ok(AnimationControl {
+            num_frames: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            num_plays: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+        })
+    }
 }
 
 #[allow(dead_code)]
 impl Png {
-    pub const STANDARD_HEADER: [u8; 8] = [
-        137 as u8, 80 as u8, 78 as u8, 71 as u8, 13 as u8, 10 as u8, 26 as u8, 10 as u8,
-    ];
+    pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
     pub fn from_chunks(chunks: Vec<Chunk>) -> Png {
         Png {
             standard_header: Png::STANDARD_HEADER,
             chunks,
+            trailing_data: Vec::new(),
         }
     }
-    pub fn append_chunk(&mut self, chunk: Chunk) {
-        self.chunks.push(chunk);
+    /// Builds a PNG from chunks, failing unless they start with IHDR and end with IEND
+    pub fn new(chunks: Vec<Chunk>) -> Result<Png> {
+        let png = Png::from_chunks(chunks);
+        if !png.is_well_ordered() {
+            bail!(PngError::InvalidOrder);
+        }
+        Ok(png)
+    }
+    /// Builds a minimal PNG container holding only the IHDR of a 1x1 RGBA image and IEND
+    pub fn new_empty() -> Png {
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&1u32.to_be_bytes());
+        header.extend_from_slice(&1u32.to_be_bytes());
+        // Bit depth 8, color type RGBA, default compression, filter and no interlace
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+        Png::from_chunks(vec![
+            Chunk::new(ChunkType::new_unchecked(*b"IHDR"), header),
+            Chunk::new(ChunkType::new_unchecked(*b"IEND"), Vec::new()),
+        ])
+    }
+    /// Appends a chunk, placing it before IEND so IEND stays the last chunk.
+    /// Returns the index the chunk was placed at
+    pub fn append_chunk(&mut self, chunk: Chunk) -> usize {
+        let index = self.iend_position().unwrap_or(self.chunks.len());
+        self.chunks.insert(index, chunk);
+        index
+    }
+    /// Appends a chunk to serialized PNG bytes without parsing or validating the existing
+    /// chunks, which is much faster on large files. The chunk is placed before the final IEND
+    pub fn append_raw(png_bytes: &[u8], chunk: &Chunk) -> Result<Vec<u8>> {
+        if !png_bytes.starts_with(&Png::STANDARD_HEADER) {
+            bail!(PngError::InvalidSignature);
+        }
+        let iend = Chunk::new(ChunkType::new_unchecked(*b"IEND"), Vec::new()).as_bytes();
+        if !png_bytes.ends_with(&iend) {
+            bail!(PngError::InvalidOrder);
+        }
+        let split = png_bytes.len() - iend.len();
+        let mut bytes = Vec::with_capacity(png_bytes.len() + chunk.byte_len());
+        bytes.extend_from_slice(&png_bytes[..split]);
+        bytes.extend_from_slice(&chunk.as_bytes());
+        bytes.extend_from_slice(&iend);
+        Ok(bytes)
+    }
+    /// Inserts a chunk at the given index, shifting later chunks back.
+    /// Fails if the index would place the chunk after IEND
+    pub fn insert_chunk(&mut self, index: usize, chunk: Chunk) -> Result<()> {
+        if index > self.chunks.len() {
+            bail!(PngError::IndexOutOfRange {
+                index,
+                len: self.chunks.len()
+            });
+        }
+        if self.iend_position().is_some_and(|iend| index > iend) {
+            bail!(PngError::InvalidOrder);
+        }
+        self.chunks.insert(index, chunk);
+        Ok(())
     }
     pub fn remove_chunk(&mut self, chunk_type: &str) -> Result<Chunk> {
         for (i, chunk) in self.chunks.iter().enumerate() {
-            match chunk.chunk_type().to_string() == chunk_type {
+            match chunk.type_str() == chunk_type {
                 true => {
                     return Ok(self.chunks.remove(i));
                 }
                 false => continue,
             }
         }
-        bail!("Not able to find chunks")
+        bail!(PngError::ChunkNotFound(chunk_type.to_string()))
+    }
+    /// Removes the first chunk of the given type, comparing type bytes directly
+    pub fn remove_chunk_by_chunk_type(&mut self, chunk_type: &ChunkType) -> Result<Chunk> {
+        match self
+            .chunks
+            .iter()
+            .position(|ch| ch.chunk_type() == chunk_type)
+        {
+            Some(i) => Ok(self.chunks.remove(i)),
+            None => bail!(PngError::ChunkNotFound(chunk_type.to_string())),
+        }
+    }
+    /// Removes the chunk at the given position, refusing critical chunks such as IHDR or IDAT
+    pub fn remove_chunk_at(&mut self, index: usize) -> Result<Chunk> {
+        let chunk = match self.chunks.get(index) {
+            Some(chunk) => chunk,
+            None => bail!(PngError::IndexOutOfRange {
+                index,
+                len: self.chunks.len()
+            }),
+        };
+        if chunk.is_critical() {
+            bail!(PngError::CriticalChunk(chunk.type_str()));
+        }
+        Ok(self.chunks.remove(index))
+    }
+    /// Replaces the data of the first chunk of the given type, keeping its position
+    pub fn replace_chunk(&mut self, chunk_type: &str, data: Vec<u8>) -> Result<()> {
+        match self.chunk_by_type_mut(chunk_type) {
+            Some(chunk) => {
+                chunk.set_data(data);
+                Ok(())
+            }
+            None => bail!(PngError::ChunkNotFound(chunk_type.to_string())),
+        }
+    }
+    /// Removes every chunk of the given type and returns how many were removed
+    pub fn remove_chunks(&mut self, chunk_type: &str) -> usize {
+        let before = self.chunks.len();
+        self.retain_chunks(|chunk| chunk.type_str() != chunk_type);
+        before - self.chunks.len()
+    }
+    /// Keeps only the chunks for which the predicate returns true, in their original order
+    pub fn retain_chunks<F: FnMut(&Chunk) -> bool>(&mut self, f: F) {
+        self.chunks.retain(f);
+    }
+    /// Removes ancillary chunks identical to an earlier chunk, optionally only of one type.
+    /// Critical chunks are never removed. Returns how many duplicates were removed
+    pub fn dedup_chunks(&mut self, chunk_type: Option<&ChunkType>) -> usize {
+        let before = self.chunks.len();
+        let mut kept: Vec<Chunk> = Vec::with_capacity(before);
+        for chunk in self.chunks.drain(..) {
+            let in_scope = chunk_type.is_none_or(|ty| chunk.chunk_type() == ty);
+            if chunk.is_ancillary() && in_scope && kept.contains(&chunk) {
+                continue;
+            }
+            kept.push(chunk);
+        }
+        self.chunks = kept;
+        before - self.chunks.len()
+    }
+    /// Bytes after the IEND chunk that do not parse as chunks, empty for a file
+    /// that ends with its last chunk
+    pub fn trailing_data(&self) -> &[u8] {
+        &self.trailing_data
     }
     pub fn header(&self) -> &[u8; 8] {
         &self.standard_header
@@ -40,61 +188,341 @@ impl Png {
     pub fn chunks(&self) -> &[Chunk] {
         &self.chunks
     }
+    /// Mutable access to the chunks, edit data through `Chunk::set_data` to keep CRCs valid
+    pub fn chunks_mut(&mut self) -> &mut [Chunk] {
+        &mut self.chunks
+    }
+    /// Consumes the PNG and returns its chunks without cloning them
+    pub fn into_chunks(self) -> Vec<Chunk> {
+        self.chunks
+    }
     pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
+        self.chunks.iter().find(|&ch| ch.type_str() == chunk_type)
+    }
+    /// Returns the index of the first chunk of the given type
+    pub fn position_of(&self, chunk_type: &str) -> Option<usize> {
+        self.chunks
+            .iter()
+            .position(|ch| ch.type_str() == chunk_type)
+    }
+    /// Mutable access to the first chunk of the given type, edit it through `Chunk::set_data`
+    pub fn chunk_by_type_mut(&mut self, chunk_type: &str) -> Option<&mut Chunk> {
+        self.chunks
+            .iter_mut()
+            .find(|ch| ch.type_str() == chunk_type)
+    }
+    /// Finds the first chunk of the given type by comparing type bytes,
+    /// avoiding the string allocation and lossy conversion of `chunk_by_type`
+    pub fn chunk_by_chunk_type(&self, chunk_type: &ChunkType) -> Option<&Chunk> {
+        self.chunks.iter().find(|&ch| ch.chunk_type() == chunk_type)
+    }
+    /// Returns every chunk of the given type in file order
+    pub fn chunks_by_type(&self, chunk_type: &str) -> Vec<&Chunk> {
         self.chunks
             .iter()
-            .find(|&ch| ch.chunk_type().to_string() == chunk_type)
+            .filter(|&ch| ch.type_str() == chunk_type)
+            .collect()
     }
 
-    pub fn as_bytes(&self) -> Vec<u8> {
-        let header_vec = self.standard_header.to_vec();
-        let chunks_vec: Vec<u8> = self
-            .chunks
+    /// Recomputes each chunk's CRC and reports `(index, type, matches stored CRC)`
+    pub fn verify_crcs(&self) -> Vec<(usize, ChunkType, bool)> {
+        self.chunks
             .iter()
-            .flat_map(|chunk| chunk.as_bytes())
-            .collect();
-        [header_vec, chunks_vec].concat()
+            .enumerate()
+            .map(|(i, chunk)| (i, *chunk.chunk_type(), chunk.crc_is_valid()))
+            .collect()
+    }
+    /// Returns true if IHDR is the first chunk, IEND is the last, and neither appears elsewhere
+    pub fn is_well_ordered(&self) -> bool {
+        let positions = |name: &[u8; 4]| {
+            self.chunks
+                .iter()
+                .enumerate()
+                .filter(|(_, chunk)| &chunk.type_bytes() == name)
+                .map(|(i, _)| i)
+                .collect::<Vec<usize>>()
+        };
+        positions(b"IHDR") == [0] && positions(b"IEND") == [self.chunks.len() - 1]
+    }
+    /// Checks the chunk ordering rules of the spec: IHDR comes first and only once, IEND
+    /// comes last and only once, at most one PLTE appears and it precedes the image data,
+    /// and the IDAT chunks are consecutive. Fails with the first violation found
+    pub fn validate_order(&self) -> Result<()> {
+        let violation = |message: String| -> Result<()> { bail!(PngError::ChunkOrder(message)) };
+        let types: Vec<[u8; 4]> = self.chunks.iter().map(|chunk| chunk.type_bytes()).collect();
+        match types.first() {
+            None => return violation("the file has no chunks".to_string()),
+            Some(b"IHDR") => {}
+            Some(_) => {
+                return violation(format!(
+                    "IHDR must be the first chunk, found {} at index 0",
+                    self.chunks[0].chunk_type()
+                ))
+            }
+        }
+        let last = types.len() - 1;
+        let mut palette = None;
+        let mut image_data: Option<(usize, usize)> = None;
+        for (index, chunk_type) in types.iter().enumerate() {
+            match chunk_type {
+                b"IHDR" if index != 0 => {
+                    return violation(format!("IHDR appears again at index {}", index))
+                }
+                b"IEND" if index != last => {
+                    return violation(format!(
+                        "IEND at index {} must be the last chunk, {} chunks follow it",
+                        index,
+                        last - index
+                    ))
+                }
+                b"PLTE" => match (palette, image_data) {
+                    (Some(first), _) => {
+                        return violation(format!(
+                            "PLTE appears again at index {}, the first is at index {}",
+                            index, first
+                        ))
+                    }
+                    (None, Some((first, _))) => {
+                        return violation(format!(
+                            "PLTE at index {} must come before the first IDAT at index {}",
+                            index, first
+                        ))
+                    }
+                    (None, None) => palette = Some(index),
+                },
+                b"IDAT" => match image_data {
+                    Some((_, previous)) if previous + 1 != index => {
+                        let before = self.chunks[index - 1].chunk_type();
+                        return violation(format!(
+                            "IDAT chunks must be consecutive, IDAT at index {} follows {} at index {}",
+                            index,
+                            before,
+                            index - 1
+                        ));
+                    }
+                    Some((first, _)) => image_data = Some((first, index)),
+                    None => image_data = Some((index, index)),
+                },
+                _ => {}
+            }
+        }
+        match &types[last] {
+            b"IEND" => Ok(()),
+            _ => violation(format!(
+                "IEND must be the last chunk, found {} at index {}",
+                self.chunks[last].chunk_type(),
+                last
+            )),
+        }
+    }
+    /// Parses the `acTL` chunk, returning None for a still image without one
+    pub fn animation_control(&self) -> Result<Option<AnimationControl>> {
+        match self.chunk_by_chunk_type(&ChunkType::new_unchecked(*b"acTL")) {
+            Some(chunk) => AnimationControl::try_from(chunk).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn iend_position(&self) -> Option<usize> {
+        self.chunks
+            .iter()
+            .position(|chunk| &chunk.type_bytes() == b"IEND")
+    }
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+    /// Size of the serialized PNG in bytes, computed without serializing it
+    pub fn total_size(&self) -> usize {
+        self.standard_header.len()
+            + self
+                .chunks
+                .iter()
+                .map(|chunk| chunk.byte_len())
+                .sum::<usize>()
+            + self.trailing_data.len()
     }
-}
 
-impl TryFrom<&[u8]> for Png {
-    type Error = Error;
+    /// Serializes the PNG into a buffer sized up front from `total_size`,
+    /// trailing data found when parsing is written back after the last chunk
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.total_size());
+        bytes.extend_from_slice(&self.standard_header);
+        for chunk in &self.chunks {
+            bytes.extend_from_slice(&chunk.length().to_be_bytes());
+            bytes.extend_from_slice(&chunk.type_bytes());
+            bytes.extend_from_slice(chunk.data());
+            bytes.extend_from_slice(&chunk.crc().to_be_bytes());
+        }
+        bytes.extend_from_slice(&self.trailing_data);
+        bytes
+    }
 
-    fn try_from(value: &[u8]) -> Result<Self> {
+    /// Returns true if the bytes start with the PNG signature, without parsing any chunks
+    pub fn is_png(bytes: &[u8]) -> bool {
+        bytes.starts_with(&Png::STANDARD_HEADER)
+    }
+    /// Reads only the 8 signature bytes and checks them, input shorter than that is not a PNG
+    pub fn is_png_reader<R: Read>(mut reader: R) -> Result<bool> {
+        let mut signature = [0; 8];
+        match reader.read_exact(&mut signature) {
+            std::result::Result::Ok(()) => Ok(Png::is_png(&signature)),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Counts chunks by walking their headers and skipping the data and CRC,
+    /// without building any `Chunk` or checking CRCs
+    pub fn count_chunks_fast<R: Read>(mut reader: R) -> Result<usize> {
+        let mut signature = [0; 8];
+        reader.read_exact(&mut signature)?;
+        if signature != Png::STANDARD_HEADER {
+            bail!(PngError::InvalidSignature);
+        }
+
+        let mut count = 0;
+        loop {
+            let mut header = [0; Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH];
+            let mut filled = 0;
+            while filled < header.len() {
+                match reader.read(&mut header[filled..])? {
+                    0 => break,
+                    read => filled += read,
+                }
+            }
+            match filled {
+                0 => return Ok(count),
+                8 => {}
+                _ => bail!(PngError::TruncatedChunk),
+            }
+
+            let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+            let skip = length as u64 + Chunk::CRC_LENGTH as u64;
+            let skipped = io::copy(&mut reader.by_ref().take(skip), &mut io::sink())?;
+            if skipped != skip {
+                bail!(PngError::TruncatedChunk);
+            }
+            count += 1;
+        }
+    }
+
+    /// Parses a PNG while trusting every stored CRC instead of recomputing it.
+    /// Only use this on files you already trust, since corruption goes unnoticed.
+    pub fn try_from_trusted(value: &[u8]) -> Result<Png> {
+        Png::parse(value, false)
+    }
+
+    fn parse(value: &[u8], check_crc: bool) -> Result<Png> {
+        let (spans, trailing) = Png::chunk_spans(value, check_crc)?;
+        let chunks = spans
+            .into_iter()
+            .map(|span| Chunk::try_from_trusted(&value[span]))
+            .collect::<Result<Vec<Chunk>>>()?;
+        let mut png = Png::from_chunks(chunks);
+        png.trailing_data = value[trailing..].to_vec();
+        Ok(png)
+    }
+
+    /// Walks the chunks after the signature, checking each one without copying it.
+    /// Returns the byte range of every chunk and where the trailing data starts
+    fn chunk_spans(value: &[u8], check_crc: bool) -> Result<(Vec<Range<usize>>, usize)> {
         if value.len() < 10 {
-            bail!("Total number of bytes less than 10! Require 10 or more!");
+            bail!(PngError::InvalidSignature);
         }
         let input_headers: [u8; 8] = value[..Png::STANDARD_HEADER.len()].try_into().unwrap();
         if input_headers != Png::STANDARD_HEADER {
-            bail!("Incorrect headers!");
+            bail!(PngError::InvalidSignature);
         }
 
         let mut index = Png::STANDARD_HEADER.len();
-        let mut chunks = Vec::new();
+        let mut spans = Vec::new();
+        let mut seen_iend = false;
         while index < value.len() {
             let data = &value[index..];
-            let chunk = Chunk::try_from(data).expect("Out of bound?");
-            index = index + Chunk::META_BYTES + chunk.length() as usize;
-            chunks.push(chunk);
+            // Older versions of pngme appended chunks after IEND, so those are still read,
+            // but bytes there that do not form a chunk are kept aside as trailing data
+            let len = match (chunk::chunk_len(data, check_crc), seen_iend) {
+                (std::result::Result::Ok(len), _) => len,
+                (Err(_), true) => break,
+                (Err(error), false) => return Err(error),
+            };
+            seen_iend |=
+                &data[Chunk::DATA_LENGTH..Chunk::DATA_LENGTH + Chunk::CHUNK_TYPE_LENGTH] == b"IEND";
+            spans.push(index..index + len);
+            index += len;
         }
-        Ok(Self::from_chunks(chunks))
+        Ok((spans, index))
     }
 }
 
-impl Display for Png {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let chunks = self
-            .chunks
+impl TryFrom<&[u8]> for Png {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        Png::parse(value, true)
+    }
+}
+
+/// Parses an owned buffer. Every chunk needs a buffer of its own, so the largest
+/// chunk, or the trailing data if that is larger, takes over the input buffer and
+/// only the rest is copied. For a typical image that moves the bulk of the IDAT data
+impl TryFrom<Vec<u8>> for Png {
+    type Error = Error;
+
+    fn try_from(mut value: Vec<u8>) -> Result<Self> {
+        let (spans, trailing) = Png::chunk_spans(&value, true)?;
+        let largest = spans
             .iter()
-            .map(|ch| ch.to_string())
-            .collect::<Vec<String>>();
+            .enumerate()
+            .max_by_key(|(_, span)| span.len())
+            .filter(|(_, span)| span.len() >= value.len() - trailing)
+            .map(|(i, _)| i);
+        let mut chunks = Vec::with_capacity(spans.len());
+        for (i, span) in spans.iter().enumerate() {
+            if Some(i) != largest {
+                chunks.push(Chunk::try_from_trusted(&value[span.clone()])?);
+            }
+        }
+        let trailing_data = match largest {
+            Some(i) => {
+                let trailing_data = value[trailing..].to_vec();
+                value.truncate(spans[i].end);
+                value.drain(..spans[i].start);
+                chunks.insert(i, Chunk::try_from_trusted_vec(value)?);
+                trailing_data
+            }
+            None => {
+                value.drain(..trailing);
+                value
+            }
+        };
+        let mut png = Png::from_chunks(chunks);
+        png.trailing_data = trailing_data;
+        Ok(png)
+    }
+}
 
+/// A header line with the signature status and chunk count, then one line per chunk
+impl Display for Png {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let signature = match self.standard_header == Png::STANDARD_HEADER {
+            true => "ok",
+            false => "invalid",
+        };
         write!(
             f,
-            "{:?}{:?}",
-            String::from_utf8(self.standard_header.to_vec()),
-            chunks.join("")
-        )
+            "PNG signature {}, {} chunks",
+            signature,
+            self.chunks.len()
+        )?;
+        self.chunks.iter().enumerate().try_for_each(|(i, chunk)| {
+            write!(
+                f,
+                "\n{}: {} length {}",
+                i,
+                chunk.chunk_type(),
+                chunk.length()
+            )
+        })
     }
 }
 
@@ -104,15 +532,14 @@ mod tests {
     use crate::chunk::Chunk;
     use crate::chunk_type::ChunkType;
     use std::convert::TryFrom;
+    use std::str::FromStr;
 
     fn testing_chunks() -> Vec<Chunk> {
-        let mut chunks = Vec::new();
-
-        chunks.push(chunk_from_strings("FrSt", "I am the first chunk").unwrap());
-        chunks.push(chunk_from_strings("miDl", "I am another chunk").unwrap());
-        chunks.push(chunk_from_strings("LASt", "I am the last chunk").unwrap());
-
-        chunks
+        vec![
+            chunk_from_strings("FrSt", "I am the first chunk").unwrap(),
+            chunk_from_strings("miDl", "I am another chunk").unwrap(),
+            chunk_from_strings("LASt", "I am the last chunk").unwrap(),
+        ]
     }
 
     fn testing_png() -> Png {
@@ -121,8 +548,6 @@ mod tests {
     }
 
     fn chunk_from_strings(chunk_type: &str, data: &str) -> Result<Chunk> {
-        use std::str::FromStr;
-
         let chunk_type = ChunkType::from_str(chunk_type)?;
         let data: Vec<u8> = data.bytes().collect();
 
@@ -137,6 +562,41 @@ mod tests {
         assert_eq!(png.chunks().len(), 3);
     }
 
+    #[test]
+    fn test_new_requires_ihdr_and_iend() {
+        assert!(Png::new(testing_chunks()).is_err());
+        assert!(Png::new(Vec::new()).is_err());
+
+        let png = Png::new(vec![
+            chunk_from_strings("IHDR", "").unwrap(),
+            chunk_from_strings("ruSt", "Message").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(png.chunk_count(), 3);
+    }
+
+    #[test]
+    fn test_new_empty() {
+        let mut png = Png::new_empty();
+        assert!(png.is_well_ordered());
+        assert_eq!(png.chunks()[0].length(), 13);
+        png.append_chunk(chunk_from_strings("ruSt", "Message").unwrap());
+
+        let bytes = png.as_bytes();
+        assert_eq!(bytes.len(), 8 + 12 + 13 + 12 + 7 + 12);
+        let parsed = Png::try_from(bytes.as_slice()).unwrap();
+        assert!(parsed.is_well_ordered());
+        assert_eq!(
+            &parsed
+                .chunk_by_type("ruSt")
+                .unwrap()
+                .data_as_string()
+                .unwrap(),
+            "Message"
+        );
+    }
+
     #[test]
     fn test_valid_from_bytes() {
         let chunk_bytes: Vec<u8> = testing_chunks()
@@ -173,6 +633,34 @@ mod tests {
         assert!(png.is_err());
     }
 
+    #[test]
+    fn test_png_error_variants() {
+        let error = Png::try_from(&PNG_FILE[1..]).err().unwrap();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::InvalidSignature)
+        );
+
+        let mut png = testing_png();
+        let error = png.remove_chunk("NoNe").unwrap_err();
+        match error.downcast_ref::<PngError>() {
+            Some(PngError::ChunkNotFound(chunk_type)) => assert_eq!(chunk_type, "NoNe"),
+            other => panic!("unexpected error {:?}", other),
+        }
+        let error = png
+            .insert_chunk(9, chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::IndexOutOfRange { index: 9, len: 3 })
+        );
+        let error = Png::new(testing_chunks()).err().unwrap();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::InvalidOrder)
+        );
+    }
+
     #[test]
     fn test_invalid_chunk() {
         let mut chunk_bytes: Vec<u8> = testing_chunks()
@@ -202,6 +690,39 @@ mod tests {
         assert_eq!(chunks.len(), 3);
     }
 
+    #[test]
+    fn test_chunks_mut() {
+        let mut png = testing_png();
+        for chunk in png.chunks_mut() {
+            let mut data = chunk.data().to_vec();
+            data.extend_from_slice(b" (edited)");
+            chunk.set_data(data);
+        }
+        let reparsed = Png::try_from(png.as_bytes().as_slice()).unwrap();
+        let chunk = reparsed.chunk_by_type("miDl").unwrap();
+        assert_eq!(
+            &chunk.data_as_string().unwrap(),
+            "I am another chunk (edited)"
+        );
+        assert_eq!(chunk.length(), 27);
+    }
+
+    #[test]
+    fn test_into_chunks() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let count = png.chunk_count();
+        let survivors: Vec<Chunk> = png
+            .into_chunks()
+            .into_iter()
+            .filter(|chunk| chunk.is_critical())
+            .collect();
+        assert!(survivors.len() < count);
+
+        let rebuilt = Png::new(survivors).unwrap();
+        assert!(rebuilt.chunks().iter().all(|chunk| chunk.is_critical()));
+        assert!(Png::try_from(rebuilt.as_bytes().as_slice()).is_ok());
+    }
+
     #[test]
     fn test_chunk_by_type() {
         let png = testing_png();
@@ -210,6 +731,36 @@ mod tests {
         assert_eq!(&chunk.data_as_string().unwrap(), "I am the first chunk");
     }
 
+    #[test]
+    fn test_chunk_by_chunk_type() {
+        let mut png = testing_png();
+        let non_ascii = ChunkType::new_unchecked([200, 117, 83, 116]);
+        png.append_chunk(Chunk::new(non_ascii, b"Message".to_vec()));
+
+        // The string form re-encodes byte 200 as a two byte UTF-8 character
+        assert_ne!(non_ascii.to_string().as_bytes(), &non_ascii.bytes()[..]);
+        let chunk = png.chunk_by_chunk_type(&non_ascii).unwrap();
+        assert_eq!(&chunk.data_as_string().unwrap(), "Message");
+
+        let removed = png.remove_chunk_by_chunk_type(&non_ascii).unwrap();
+        assert_eq!(removed.chunk_type(), &non_ascii);
+        assert!(png.chunk_by_chunk_type(&non_ascii).is_none());
+        assert!(png.remove_chunk_by_chunk_type(&non_ascii).is_err());
+    }
+
+    #[test]
+    fn test_chunks_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message 1").unwrap());
+        png.append_chunk(chunk_from_strings("FrSt", "Other").unwrap());
+        png.append_chunk(chunk_from_strings("TeSt", "Message 2").unwrap());
+        let chunks = png.chunks_by_type("TeSt");
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(&chunks[0].data_as_string().unwrap(), "Message 1");
+        assert_eq!(&chunks[1].data_as_string().unwrap(), "Message 2");
+        assert!(png.chunks_by_type("NoNe").is_empty());
+    }
+
     #[test]
     fn test_append_chunk() {
         let mut png = testing_png();
@@ -219,6 +770,151 @@ mod tests {
         assert_eq!(&chunk.data_as_string().unwrap(), "Message");
     }
 
+    #[test]
+    fn test_append_chunk_lands_before_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let count = png.chunk_count();
+        let index = png.append_chunk(chunk_from_strings("ruSt", "Message").unwrap());
+        assert_eq!(index, count - 1);
+        assert_eq!(&png.chunks()[count - 1].chunk_type().to_string(), "ruSt");
+        assert_eq!(&png.chunks()[count].chunk_type().to_string(), "IEND");
+        assert!(png.is_well_ordered());
+
+        let reparsed = Png::try_from(png.as_bytes().as_slice()).unwrap();
+        assert!(reparsed.is_well_ordered());
+    }
+
+    #[test]
+    fn test_append_raw_matches_append_chunk() {
+        let chunk = chunk_from_strings("ruSt", "Message").unwrap();
+        let bytes = Png::append_raw(&PNG_FILE, &chunk).unwrap();
+
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.append_chunk(chunk_from_strings("ruSt", "Message").unwrap());
+        assert_eq!(bytes, png.as_bytes());
+
+        assert!(Png::append_raw(&PNG_FILE[1..], &chunk).is_err());
+        let no_iend = testing_png().as_bytes();
+        assert!(Png::append_raw(&no_iend, &chunk).is_err());
+    }
+
+    #[test]
+    fn test_is_well_ordered() {
+        assert!(Png::try_from(&PNG_FILE[..]).unwrap().is_well_ordered());
+        assert!(!testing_png().is_well_ordered());
+        assert!(!Png::from_chunks(Vec::new()).is_well_ordered());
+
+        let unordered = Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+            chunk_from_strings("ruSt", "Message").unwrap(),
+        ]);
+        assert!(!unordered.is_well_ordered());
+    }
+
+    #[test]
+    fn test_validate_order() {
+        let png = |types: &[&str]| {
+            Png::from_chunks(
+                types
+                    .iter()
+                    .map(|chunk_type| chunk_from_strings(chunk_type, "").unwrap())
+                    .collect(),
+            )
+        };
+        let violation = |types: &[&str]| match png(types).validate_order() {
+            std::result::Result::Ok(()) => panic!("{:?} should be out of order", types),
+            Err(error) => match error.downcast_ref::<PngError>() {
+                Some(PngError::ChunkOrder(violation)) => violation.clone(),
+                other => panic!("unexpected error {:?}", other),
+            },
+        };
+
+        assert!(Png::try_from(&PNG_FILE[..])
+            .unwrap()
+            .validate_order()
+            .is_ok());
+        png(&["IHDR", "PLTE", "tEXt", "IDAT", "IDAT", "ruSt", "IEND"])
+            .validate_order()
+            .unwrap();
+
+        assert_eq!(violation(&[]), "the file has no chunks");
+        assert_eq!(
+            violation(&["tEXt", "IHDR", "IEND"]),
+            "IHDR must be the first chunk, found tEXt at index 0"
+        );
+        assert_eq!(
+            violation(&["IHDR", "IDAT", "PLTE", "IEND"]),
+            "PLTE at index 2 must come before the first IDAT at index 1"
+        );
+        assert_eq!(
+            violation(&["IHDR", "PLTE", "PLTE", "IEND"]),
+            "PLTE appears again at index 2, the first is at index 1"
+        );
+        assert_eq!(
+            violation(&["IHDR", "IDAT", "ruSt", "IDAT", "IEND"]),
+            "IDAT chunks must be consecutive, IDAT at index 3 follows ruSt at index 2"
+        );
+        assert_eq!(
+            violation(&["IHDR", "IEND", "ruSt"]),
+            "IEND at index 1 must be the last chunk, 1 chunks follow it"
+        );
+        assert_eq!(
+            violation(&["IHDR", "IDAT"]),
+            "IEND must be the last chunk, found IDAT at index 1"
+        );
+    }
+
+    #[test]
+    fn test_animation_control() {
+        let mut png = Png::new_empty();
+        assert_eq!(png.animation_control().unwrap(), None);
+
+        let mut data = 3u32.to_be_bytes().to_vec();
+        data.extend_from_slice(&0u32.to_be_bytes());
+        png.insert_chunk(1, Chunk::new(ChunkType::from_str("acTL").unwrap(), data))
+            .unwrap();
+        assert_eq!(
+            png.animation_control().unwrap(),
+            Some(AnimationControl {
+                num_frames: 3,
+                num_plays: 0
+            })
+        );
+
+        png.replace_chunk("acTL", vec![0; 5]).unwrap();
+        assert!(png.animation_control().is_err());
+    }
+
+    #[test]
+    fn test_insert_chunk_after_iend() {
+        let mut png = Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+        ]);
+        assert!(png
+            .insert_chunk(2, chunk_from_strings("ruSt", "Message").unwrap())
+            .is_err());
+        png.insert_chunk(1, chunk_from_strings("ruSt", "Message").unwrap())
+            .unwrap();
+        assert!(png.is_well_ordered());
+    }
+
+    #[test]
+    fn test_insert_chunk() {
+        let mut png = testing_png();
+        png.insert_chunk(1, chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+        assert_eq!(&png.chunks()[1].chunk_type().to_string(), "TeSt");
+        assert_eq!(&png.chunks()[2].chunk_type().to_string(), "miDl");
+        png.insert_chunk(4, chunk_from_strings("TeSt", "End").unwrap())
+            .unwrap();
+        assert_eq!(png.chunks().len(), 5);
+        assert!(png
+            .insert_chunk(6, chunk_from_strings("TeSt", "Far").unwrap())
+            .is_err());
+    }
+
     #[test]
     fn test_remove_chunk() {
         let mut png = testing_png();
@@ -228,20 +924,325 @@ mod tests {
         assert!(chunk.is_none());
     }
 
+    #[test]
+    fn test_remove_chunk_at() {
+        let mut png = Png::from_chunks(vec![
+            chunk_from_strings("frSt", "first").unwrap(),
+            chunk_from_strings("miDl", "middle").unwrap(),
+            chunk_from_strings("laSt", "last").unwrap(),
+        ]);
+        let removed = png.remove_chunk_at(1).unwrap();
+        assert_eq!(&removed.data_as_string().unwrap(), "middle");
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["frSt", "laSt"]);
+
+        let error = png.remove_chunk_at(2).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::IndexOutOfRange { index: 2, len: 2 })
+        );
+    }
+
+    #[test]
+    fn test_remove_chunk_at_refuses_critical_chunks() {
+        let mut png = Png::new_empty();
+        let error = png.remove_chunk_at(0).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<PngError>(),
+            Some(&PngError::CriticalChunk("IHDR".to_string()))
+        );
+        assert_eq!(png.chunk_count(), 2);
+    }
+
+    #[test]
+    fn test_remove_chunks() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message 1").unwrap());
+        png.append_chunk(chunk_from_strings("TeSt", "Message 2").unwrap());
+        png.append_chunk(chunk_from_strings("TeSt", "Message 3").unwrap());
+        assert_eq!(png.remove_chunks("TeSt"), 3);
+        assert!(png.chunk_by_type("TeSt").is_none());
+        assert_eq!(png.chunks().len(), 3);
+        assert_eq!(png.remove_chunks("TeSt"), 0);
+    }
+
+    #[test]
+    fn test_replace_chunk() {
+        let mut png = testing_png();
+        png.replace_chunk("miDl", b"Replaced".to_vec()).unwrap();
+        let chunk = &png.chunks()[1];
+        assert_eq!(&chunk.chunk_type().to_string(), "miDl");
+        assert_eq!(&chunk.data_as_string().unwrap(), "Replaced");
+        assert_eq!(png.chunks().len(), 3);
+        assert!(png.replace_chunk("NoNe", Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_position_of() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("miDl", "Another middle chunk").unwrap());
+        assert_eq!(png.position_of("FrSt"), Some(0));
+        assert_eq!(png.position_of("miDl"), Some(1));
+        assert_eq!(png.position_of("LASt"), Some(2));
+        assert_eq!(png.position_of("NoNe"), None);
+    }
+
+    #[test]
+    fn test_chunk_by_type_mut() {
+        let mut png = testing_png();
+        png.chunk_by_type_mut("miDl")
+            .unwrap()
+            .set_data(b"Updated".to_vec());
+        assert!(png.chunk_by_type_mut("NoNe").is_none());
+
+        let expected = chunk_from_strings("miDl", "Updated").unwrap().as_bytes();
+        let bytes = png.as_bytes();
+        assert!(bytes
+            .windows(expected.len())
+            .any(|window| window == expected));
+        assert_eq!(
+            Png::try_from(bytes.as_slice()).unwrap().chunks()[1].data(),
+            b"Updated"
+        );
+    }
+
+    #[test]
+    fn test_trailing_data() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.trailing_data().is_empty());
+
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend_from_slice(b"hidden after IEND");
+        let png = Png::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(png.trailing_data(), b"hidden after IEND");
+        assert_eq!(
+            png.chunks().last().unwrap().chunk_type().to_string(),
+            "IEND"
+        );
+        let trusted = Png::try_from_trusted(bytes.as_slice()).unwrap();
+        assert_eq!(trusted.trailing_data(), b"hidden after IEND");
+
+        // A well formed chunk after IEND is still read as a chunk
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend_from_slice(&chunk_from_strings("ruSt", "late").unwrap().as_bytes());
+        bytes.extend_from_slice(b"tail");
+        let png = Png::try_from(bytes.as_slice()).unwrap();
+        assert!(png.chunk_by_type("ruSt").is_some());
+        assert_eq!(png.trailing_data(), b"tail");
+    }
+
+    #[test]
+    fn test_trailing_data_round_trip() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend_from_slice(b"hidden after IEND");
+        let mut png = Png::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(png.total_size(), bytes.len());
+        assert_eq!(png.as_bytes(), bytes);
+
+        // Editing chunks keeps the trailing data at the end of the file
+        png.append_chunk(chunk_from_strings("ruSt", "edit").unwrap());
+        let edited = png.as_bytes();
+        assert!(edited.ends_with(b"hidden after IEND"));
+        let reparsed = Png::try_from(edited.as_slice()).unwrap();
+        assert_eq!(reparsed.trailing_data(), b"hidden after IEND");
+        assert!(reparsed.chunk_by_type("ruSt").is_some());
+    }
+
+    #[test]
+    fn test_is_png() {
+        assert!(Png::is_png(&PNG_FILE));
+        assert!(Png::is_png(&Png::STANDARD_HEADER));
+        assert!(!Png::is_png(&PNG_FILE[1..]));
+        assert!(!Png::is_png(b"GIF89a not a png"));
+        assert!(!Png::is_png(&[]));
+
+        assert!(Png::is_png_reader(&PNG_FILE[..]).unwrap());
+        assert!(!Png::is_png_reader(&PNG_FILE[..4]).unwrap());
+        assert!(!Png::is_png_reader(&b"\x89PNG\r\n\x1a\x0b"[..]).unwrap());
+    }
+
+    #[test]
+    fn test_retain_chunks() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.append_chunk(chunk_from_strings("ruSt", "Message").unwrap());
+        assert!(png.chunks().iter().any(|chunk| chunk.is_ancillary()));
+
+        png.retain_chunks(|chunk| chunk.is_critical());
+        assert!(png.chunks().iter().all(|chunk| chunk.is_critical()));
+        assert!(png.chunk_by_type("ruSt").is_none());
+        assert!(png.is_well_ordered());
+    }
+
+    #[test]
+    fn test_dedup_chunks() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("teSt", "Message").unwrap());
+        png.append_chunk(chunk_from_strings("teSt", "Message").unwrap());
+        png.append_chunk(chunk_from_strings("teSt", "Different").unwrap());
+        png.append_chunk(chunk_from_strings("otHr", "Message").unwrap());
+        png.append_chunk(chunk_from_strings("otHr", "Message").unwrap());
+        png.append_chunk(chunk_from_strings("FrSt", "I am the first chunk").unwrap());
+
+        let filter = ChunkType::from_str("teSt").unwrap();
+        assert_eq!(png.dedup_chunks(Some(&filter)), 1);
+        assert_eq!(png.chunks_by_type("teSt").len(), 2);
+        assert_eq!(png.chunks_by_type("otHr").len(), 2);
+
+        assert_eq!(png.dedup_chunks(None), 1);
+        assert_eq!(png.chunks_by_type("otHr").len(), 1);
+        // Critical chunks are kept even when duplicated
+        assert_eq!(png.chunks_by_type("FrSt").len(), 2);
+    }
+
     #[test]
     fn test_png_from_image_file() {
         let png = Png::try_from(&PNG_FILE[..]);
         assert!(png.is_ok());
     }
 
+    #[test]
+    fn test_count_chunks_fast() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let count = Png::count_chunks_fast(&PNG_FILE[..]).unwrap();
+        assert_eq!(count, png.chunks().len());
+    }
+
+    #[test]
+    fn test_count_chunks_fast_truncated() {
+        assert!(Png::count_chunks_fast(&PNG_FILE[..PNG_FILE.len() - 2]).is_err());
+        assert!(Png::count_chunks_fast(&PNG_FILE[1..]).is_err());
+    }
+
+    #[test]
+    fn test_total_size_and_chunk_count() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.total_size(), png.as_bytes().len());
+        assert_eq!(png.chunk_count(), png.chunks().len());
+
+        let png = testing_png();
+        assert_eq!(png.total_size(), png.as_bytes().len());
+        assert_eq!(png.chunk_count(), 3);
+    }
+
     #[test]
     fn test_as_bytes() {
         let png = Png::try_from(&PNG_FILE[..]).unwrap();
         let actual = png.as_bytes();
-        let expected: Vec<u8> = PNG_FILE.iter().copied().collect();
+        let expected: Vec<u8> = PNG_FILE.to_vec();
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_trusted_parse_matches_checked_parse() {
+        use crate::chunk::tests::CRC_COMPUTATIONS;
+
+        CRC_COMPUTATIONS.with(|count| count.set(0));
+        let checked = Png::try_from(&PNG_FILE[..]).unwrap();
+        let checked_crcs = CRC_COMPUTATIONS.with(|count| count.replace(0));
+
+        let trusted = Png::try_from_trusted(&PNG_FILE[..]).unwrap();
+        let trusted_crcs = CRC_COMPUTATIONS.with(|count| count.get());
+
+        assert_eq!(checked_crcs, checked.chunks().len());
+        assert_eq!(trusted_crcs, 0);
+        assert_eq!(checked.as_bytes(), trusted.as_bytes());
+    }
+
+    #[test]
+    fn test_trusted_round_trip_keeps_stored_crc() {
+        let mut bytes = Png::from_chunks(testing_chunks()).as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+
+        assert!(Png::try_from(bytes.as_ref()).is_err());
+        let png = Png::try_from_trusted(bytes.as_ref()).unwrap();
+        assert_eq!(png.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_verify_crcs() {
+        let mut bytes = Png::from_chunks(testing_chunks()).as_bytes();
+        // Corrupt the CRC of the second chunk, "miDl"
+        let crc_end = Png::STANDARD_HEADER.len()
+            + 2 * Chunk::META_BYTES
+            + "I am the first chunk".len()
+            + "I am another chunk".len();
+        bytes[crc_end - 1] ^= 0xff;
+
+        let png = Png::try_from_trusted(&bytes).unwrap();
+        let report = png.verify_crcs();
+        let flags: Vec<bool> = report.iter().map(|&(_, _, valid)| valid).collect();
+        assert_eq!(flags, [true, false, true]);
+        assert_eq!(report[1].0, 1);
+        assert_eq!(report[1].1, ChunkType::from_str("miDl").unwrap());
+    }
+
+    #[test]
+    fn test_huge_declared_length_is_an_error() {
+        let mut bytes = Png::new_empty().as_bytes();
+        let length = Png::STANDARD_HEADER.len();
+        bytes[length..length + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(Png::try_from(bytes.as_ref()).is_err());
+        assert!(Png::try_from_trusted(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_png_from_vec() {
+        let png = Png::try_from(PNG_FILE.to_vec()).unwrap();
+        assert_eq!(png.as_bytes(), PNG_FILE);
+        assert_eq!(png.chunks(), Png::try_from(&PNG_FILE[..]).unwrap().chunks());
+        assert!(Png::try_from(PNG_FILE[1..].to_vec()).is_err());
+
+        let mut bytes = PNG_FILE.to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        assert!(Png::try_from(bytes).is_err());
+    }
+
+    #[test]
+    fn test_png_from_vec_reuses_the_buffer() {
+        // The large chunk takes over the input buffer instead of being copied
+        let data = vec![7; 4096];
+        let bytes = Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "header").unwrap(),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), data.clone()),
+            chunk_from_strings("IEND", "").unwrap(),
+        ])
+        .as_bytes();
+        let buffer = bytes.as_ptr();
+        let png = Png::try_from(bytes).unwrap();
+        assert_eq!(png.chunks()[1].data(), data);
+        assert_eq!(png.chunks()[1].data().as_ptr(), buffer);
+
+        // Trailing data larger than every chunk keeps the buffer instead
+        let mut bytes = Png::new_empty().as_bytes();
+        bytes.extend_from_slice(&data);
+        let buffer = bytes.as_ptr();
+        let png = Png::try_from(bytes).unwrap();
+        assert_eq!(png.trailing_data(), data);
+        assert_eq!(png.trailing_data().as_ptr(), buffer);
+        assert_eq!(png.chunk_count(), 2);
+    }
+
+    #[test]
+    fn test_png_display() {
+        let png = testing_png();
+        assert_eq!(
+            png.to_string(),
+            "PNG signature ok, 3 chunks\n\
+             0: FrSt length 20\n\
+             1: miDl length 18\n\
+             2: LASt length 19"
+        );
+        let rendered = Png::try_from(&PNG_FILE[..]).unwrap().to_string();
+        assert!(rendered.contains(": IHDR length 13"));
+        assert!(rendered.contains(": IEND length 0"));
+    }
+
     #[test]
     fn test_png_trait_impls() {
         let chunk_bytes: Vec<u8> = testing_chunks()
@@ -0,0 +1,142 @@
+use anyhow::bail;
+
+use crate::{Error, Result};
+
+/// The fields of an uncompressed `iTXt` chunk: a keyword, the compression flag and
+/// method bytes, a language tag and a translated keyword, each field ending in a
+/// null separator, then the UTF-8 text
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InternationalText {
+    keyword: String,
+    language_tag: String,
+    translated_keyword: String,
+    text: String,
+}
+
+impl InternationalText {
+    /// Builds the text, failing if the keyword is not 1 to 79 bytes or a field contains a null
+    pub fn new(
+        keyword: &str,
+        language_tag: &str,
+        translated_keyword: &str,
+        text: &str,
+    ) -> Result<InternationalText> {
+        check_keyword(keyword)?;
+        if language_tag.contains('\0') || translated_keyword.contains('\0') {
+            bail!("iTXt language tag and translated keyword cannot contain null characters");
+        }
+        Ok(InternationalText {
+            keyword: keyword.to_string(),
+            language_tag: language_tag.to_string(),
+            translated_keyword: translated_keyword.to_string(),
+            text: text.to_string(),
+        })
+    }
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+    /// Language of the text, e.g. `ja` or `en-GB`, empty if unknown
+    pub fn language_tag(&self) -> &str {
+        &self.language_tag
+    }
+    /// The keyword translated into the language of the text, may be empty
+    pub fn translated_keyword(&self) -> &str {
+        &self.translated_keyword
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The `iTXt` chunk data, always written uncompressed
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            self.keyword.len()
+                + self.language_tag.len()
+                + self.translated_keyword.len()
+                + self.text.len()
+                + 5,
+        );
+        bytes.extend_from_slice(self.keyword.as_bytes());
+        bytes.extend_from_slice(&[0, 0, 0]);
+        bytes.extend_from_slice(self.language_tag.as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(self.translated_keyword.as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(self.text.as_bytes());
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for InternationalText {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        let (keyword, rest) = split_null(data)?;
+        let (compression_flag, compression_method, rest) = match rest {
+            [flag, method, rest @ ..] => (*flag, *method, rest),
+            _ => bail!("iTXt data ends before the compression fields"),
+        };
+        match (compression_flag, compression_method) {
+            (0, 0) => {}
+            (1, 0) => bail!("Compressed iTXt text is not supported"),
+            _ => bail!(
+                "Invalid iTXt compression flag {} or method {}",
+                compression_flag,
+                compression_method
+            ),
+        }
+        let (language_tag, rest) = split_null(rest)?;
+        let (translated_keyword, text) = split_null(rest)?;
+        InternationalText::new(
+            &String::from_utf8_lossy(keyword),
+            std::str::from_utf8(language_tag)?,
+            std::str::from_utf8(translated_keyword)?,
+            std::str::from_utf8(text)?,
+        )
+    }
+}
+
+/// Checks the keyword of a tEXt or iTXt chunk: 1 to 79 bytes without null characters
+pub(crate) fn check_keyword(keyword: &str) -> Result<()> {
+    if keyword.is_empty() || keyword.len() > 79 || keyword.contains('\0') {
+        bail!(
+            "Keyword must be 1 to 79 bytes without null characters, got {:?}",
+            keyword
+        );
+    }
+    Ok(())
+}
+
+/// Splits off the bytes before the next null separator
+fn split_null(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    match data.iter().position(|&byte| byte == 0) {
+        Some(separator) => Ok((&data[..separator], &data[separator + 1..])),
+        None => bail!("iTXt data is missing a null separator"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let text = InternationalText::new("Title", "ja", "タイトル", "吾輩は猫である").unwrap();
+        let bytes = text.to_bytes();
+        assert!(bytes.starts_with(b"Title\0\0\0ja\0"));
+        let parsed = InternationalText::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(parsed, text);
+        assert_eq!(parsed.language_tag(), "ja");
+        assert_eq!(parsed.translated_keyword(), "タイトル");
+        assert_eq!(parsed.text(), "吾輩は猫である");
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        assert!(InternationalText::new("", "", "", "text").is_err());
+        assert!(InternationalText::new("Title", "e\0n", "", "text").is_err());
+        assert!(InternationalText::try_from(&b"Title"[..]).is_err());
+        assert!(InternationalText::try_from(&b"Title\0\x01\0en\0\0zipped"[..]).is_err());
+        assert!(InternationalText::try_from(&b"Title\0\0\0en\0\0\xff"[..]).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt::Display;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::bail;
+
+use crate::{Error, Result};
+
+/// The UTC time stored in a `tIME` chunk: a big endian u16 year then one byte
+/// each for month, day, hour, minute and second
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ModificationTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl ModificationTime {
+    /// The current UTC time, a clock set before 1970 counts as the epoch
+    pub fn now() -> ModificationTime {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        ModificationTime::from_unix(seconds)
+    }
+
+    /// Converts seconds since the Unix epoch to a UTC civil date and time
+    pub fn from_unix(seconds: u64) -> ModificationTime {
+        let days = (seconds / 86_400) as i64;
+        let time = seconds % 86_400;
+        // Days to a proleptic Gregorian date, counting 400 year eras from 0000-03-01
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = match shifted_month < 10 {
+            true => shifted_month + 3,
+            false => shifted_month - 9,
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        ModificationTime {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (time / 3_600) as u8,
+            minute: (time / 60 % 60) as u8,
+            second: (time % 60) as u8,
+        }
+    }
+
+    /// The 7 byte `tIME` chunk data
+    pub fn to_bytes(&self) -> [u8; 7] {
+        let [high, low] = self.year.to_be_bytes();
+        [
+            high,
+            low,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+        ]
+    }
+}
+
+impl TryFrom<&[u8]> for ModificationTime {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self> {
+        let [high, low, month, day, hour, minute, second]: [u8; 7] = match data.try_into() {
+            Ok(bytes) => bytes,
+            Err(_) => bail!("tIME data must be 7 bytes, found {}", data.len()),
+        };
+        // The spec allows a second of 60 for leap seconds
+        if !(1..=12).contains(&month)
+            || !(1..=31).contains(&day)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            bail!("tIME data holds an out of range date or time");
+        }
+        Ok(ModificationTime {
+            year: u16::from_be_bytes([high, low]),
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+}
+
+impl Display for ModificationTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_unix() {
+        assert_eq!(
+            ModificationTime::from_unix(0).to_string(),
+            "1970-01-01 00:00:00 UTC"
+        );
+        assert_eq!(
+            ModificationTime::from_unix(951_782_400).to_string(),
+            "2000-02-29 00:00:00 UTC"
+        );
+        assert_eq!(
+            ModificationTime::from_unix(1_700_000_000).to_string(),
+            "2023-11-14 22:13:20 UTC"
+        );
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let time = ModificationTime::from_unix(1_700_000_000);
+        let bytes = time.to_bytes();
+        assert_eq!(bytes, [0x07, 0xe7, 11, 14, 22, 13, 20]);
+        assert_eq!(ModificationTime::try_from(&bytes[..]).unwrap(), time);
+
+        assert!(ModificationTime::try_from(&bytes[..6]).is_err());
+        assert!(ModificationTime::try_from(&[0x07, 0xe7, 13, 1, 0, 0, 0][..]).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn temp_copy(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pngme-cli-{}-{}", std::process::id(), name));
+    fs::copy(concat!(env!("CARGO_MANIFEST_DIR"), "/cat.png"), &path).unwrap();
+    path
+}
+
+fn pngme(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pngme"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_warnings_go_to_stderr() {
+    let path = temp_copy("warnings.png");
+    let output = pngme(&["--force", "encode", path.to_str().unwrap(), "ruSa", "again"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr
+        .lines()
+        .any(|line| line == "warning: kind=duplicate-type chunk_type=ruSa"));
+    assert!(!stdout.contains("warning:"));
+
+    // Bytes after IEND are carried along by encode, so they are reported too
+    let mut bytes = fs::read(&path).unwrap();
+    bytes.extend_from_slice(b"hidden");
+    fs::write(&path, bytes).unwrap();
+    let output = pngme(&["--force", "encode", path.to_str().unwrap(), "ruSt", "hi"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr
+        .lines()
+        .any(|line| line == "warning: kind=trailing-data bytes=6"));
+    assert!(!String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("warning:"));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_repeated_messages_with_an_output_file() {
+    let path = temp_copy("messages.png");
+    let output_path = path.with_extension("out.png");
+    let (path, output_path) = (path.to_str().unwrap(), output_path.to_str().unwrap());
+    let original = fs::read(path).unwrap();
+
+    // A positional output would be read as the message, so clap rejects it
+    let args = ["--force", "encode", path, "ruSt", "-m", "one", "-m", "two"];
+    let output = pngme(&[&args[..], &[output_path]].concat());
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(fs::read(path).unwrap(), original);
+    assert!(fs::metadata(output_path).is_err());
+
+    let output = pngme(&[&args[..], &["--output", output_path]].concat());
+    assert!(output.status.success());
+    assert_eq!(fs::read(path).unwrap(), original);
+    let output = pngme(&["--quiet", "decode", output_path, "ruSt", "--all"]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "one\ntwo\n");
+    fs::remove_file(path).unwrap();
+    fs::remove_file(output_path).unwrap();
+}
+
+#[test]
+fn test_placement_flags_conflict() {
+    let path = temp_copy("placement.png");
+    let path = path.to_str().unwrap();
+    let original = fs::read(path).unwrap();
+    let encode = ["--force", "encode", path, "ruSt", "hello"];
+    for flags in [
+        &["--before", "IDAT", "--after", "IHDR"][..],
+        &["--replace-if-changed", "--before", "IDAT"][..],
+        &["--replace-if-changed", "--after", "IHDR"][..],
+    ] {
+        let output = pngme(&[&encode[..], flags].concat());
+        assert_eq!(output.status.code(), Some(2), "{:?}", flags);
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("cannot be used with"));
+    }
+    assert_eq!(fs::read(path).unwrap(), original);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_quiet_decode_prints_only_the_message() {
+    let path = temp_copy("quiet-decode.png");
+    let path = path.to_str().unwrap();
+    assert!(pngme(&["--force", "encode", path, "ruSt", "hello"])
+        .status
+        .success());
+
+    let output = pngme(&["--quiet", "decode", path, "ruSt"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "hello\n");
+
+    let output = pngme(&["--quiet", "decode", path, "miSs"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(!output.stderr.is_empty());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_in_place_writes_need_force() {
+    let path = temp_copy("force.png");
+    let original = fs::read(&path).unwrap();
+    let output = pngme(&["encode", path.to_str().unwrap(), "ruSt", "hello"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("pass --force"));
+    assert_eq!(fs::read(&path).unwrap(), original);
+
+    let output_file = temp_copy("force-output.png");
+    fs::remove_file(&output_file).unwrap();
+    let output = pngme(&[
+        "encode",
+        path.to_str().unwrap(),
+        "ruSt",
+        "hello",
+        output_file.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(fs::read(&path).unwrap(), original);
+    fs::remove_file(path).unwrap();
+    fs::remove_file(output_file).unwrap();
+}
+
+#[test]
+fn test_exit_codes_reflect_the_failure() {
+    let missing = std::env::temp_dir().join("pngme-cli-missing.png");
+    let output = pngme(&["print", missing.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(3));
+
+    let garbage =
+        std::env::temp_dir().join(format!("pngme-cli-{}-garbage.png", std::process::id()));
+    fs::write(&garbage, "not a png").unwrap();
+    let output = pngme(&["print", garbage.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(4));
+    fs::remove_file(garbage).unwrap();
+
+    let path = temp_copy("exit-codes.png");
+    let output = pngme(&["decode", path.to_str().unwrap(), "miSs"]);
+    assert_eq!(output.status.code(), Some(5));
+    let output = pngme(&["decode", path.to_str().unwrap(), "Ru1t"]);
+    assert_eq!(output.status.code(), Some(2));
+    fs::remove_file(path).unwrap();
+}